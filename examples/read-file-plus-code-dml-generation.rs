@@ -16,7 +16,7 @@ pub struct MyFields {
 }
 
 pub fn read_my_fields<P: AsRef<Path>>(path: P) -> Result< MyFields, Box<dyn Error>> {
-    Ok(read_yaml_from_file(path)?)
+    read_yaml_from_file(path)
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -159,7 +159,7 @@ fn gen_ddls(spec:&MySpec) -> Vec<String> {
                     Field::new_only_db("survey_id", &FieldAttributes::new(FieldType::Int)),
                     Field::new_only_db("agregado", &FieldAttributes::new_nn_def(FieldType::Bool, "false")),
                 ];
-                let a_fields = read_my_fields(&spec.fields_file).expect(format!("Read fields from '{}'", spec.fields_file).as_str());
+                let a_fields = read_my_fields(&spec.fields_file).unwrap_or_else(|_| panic!("Read fields from '{}'", spec.fields_file));
                 for (name, attrs) in a_fields.basic.iter() {
                     if ! attrs.roster {
                         m_fields.push(Field{ name:name.to_owned(), attributes:attrs.to_owned()})
@@ -167,17 +167,12 @@ fn gen_ddls(spec:&MySpec) -> Vec<String> {
                 }
                 if let Some(foreign_keys) = &a_fields.foreign_keys {
                     for fk in foreign_keys.iter() {
-                        if has_schema(&spec, &fk.table.schema) && has_schema(&spec, &fk.ref_table.schema) {
+                        if has_schema(spec, &fk.table.schema) && has_schema(spec, &fk.ref_table.schema) {
                             processor.add(fk);
                         } else {
-                            let nfk = ForeignKey{
-                                table: ObjectPath::new_table(&spec.schema, &fk.table.name),
-                                fields: fk.fields.clone(),
-                                ref_table: ObjectPath::new_table(&spec.schema, &fk.ref_table.name),
-                                ref_fields: fk.ref_fields.clone(),
-                                on_update: fk.on_update.clone(),
-                                on_delete: fk.on_delete.clone(),
-                            };
+                            let mut nfk = fk.clone();
+                            nfk.table = ObjectPath::new_table(&spec.schema, &fk.table.name);
+                            nfk.ref_table = ObjectPath::new_table(&spec.schema, &fk.ref_table.name);
                             fks.push(nfk);
                         }
                     }
@@ -196,11 +191,7 @@ fn gen_ddls(spec:&MySpec) -> Vec<String> {
                 // println!("{}", t_main);
                 processor.add(&t_main);
 
-                indexes = if let Some(idxs) = t_main.indexes() {
-                    idxs
-                } else {
-                    Vec::new()
-                };
+                indexes = t_main.indexes().unwrap_or_default();
                 if !indexes.is_empty() {
                     for index in indexes.iter() {
                         debug!("Got index: {:?}", index);
@@ -217,7 +208,7 @@ fn gen_ddls(spec:&MySpec) -> Vec<String> {
 }
 
 pub fn print_ddls(spec:&MySpec) {
-    let sqls = gen_ddls(&spec);
+    let sqls = gen_ddls(spec);
     for sql in sqls.iter() {
         println!("{sql}")
     }
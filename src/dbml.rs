@@ -0,0 +1,595 @@
+use crate::sql::*;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+
+// DBML has no schema objects: Schema's are written as comments like
+// "// schema sales owner rw_user", which are read back by read_dbml()
+const SCHEMA_COMMENT: &str = "// schema ";
+
+type Objects = Vec<Box<dyn DBObject>>;
+
+fn quote_ident(name:&str) -> String {
+    if ! name.is_empty()
+        && ! name.starts_with(|c:char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        name.to_owned()
+    } else {
+        format!("\"{name}\"")
+    }
+}
+
+fn quote_path(path:&ObjectPath) -> String {
+    match &path.schema {
+        Some(schema) => format!("{}.{}", quote_ident(schema), quote_ident(&path.name)),
+        None => quote_ident(&path.name),
+    }
+}
+
+fn quote_str(text:&str) -> String {
+    if text.contains('\n') {
+        format!("'''{text}'''")
+    } else {
+        format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+    }
+}
+
+fn columns(fields:&[String]) -> String {
+    let names:Vec<String> = fields.iter().map(|f| quote_ident(f)).collect();
+    if names.len() == 1 {
+        names[0].to_owned()
+    } else {
+        format!("({})", names.join(", "))
+    }
+}
+
+fn dbml_type(dtype:&FieldType) -> &str {
+    match dtype {
+        FieldType::Int|FieldType::AutoInc => "int",
+        FieldType::BigInt => "bigint",
+        FieldType::Txt => "text",
+        FieldType::Dbl => "double",
+        FieldType::Bool => "bool",
+    }
+}
+
+fn dbml_default(dtype:&FieldType, defval:&str) -> String {
+    if *dtype == FieldType::Txt {
+        quote_str(defval)
+    } else if defval.parse::<f64>().is_ok() || ["true", "false", "null"].contains(&defval) {
+        defval.to_owned()
+    } else {
+        format!("`{defval}`")
+    }
+}
+
+fn dbml_fk_on(on:&FKOn) -> String {
+    on.to_string().to_lowercase()
+}
+
+fn write_field(field:&Field) -> String {
+    let att = &field.attributes;
+    let mut settings = Vec::new();
+    if att.primary_key {
+        settings.push("pk".to_owned())
+    }
+    if att.dtype == FieldType::AutoInc {
+        settings.push("increment".to_owned())
+    }
+    if ! att.empty && ! att.primary_key {
+        settings.push("not null".to_owned())
+    }
+    if att.unique {
+        settings.push("unique".to_owned())
+    }
+    if let Some(defval) = &att.defval {
+        settings.push(format!("default: {}", dbml_default(&att.dtype, defval)))
+    }
+    if let Some(comment) = &att.comment {
+        settings.push(format!("note: {}", quote_str(comment)))
+    }
+    let settings = if settings.is_empty() { "".to_owned() } else { format!(" [{}]", settings.join(", ")) };
+    format!("  {} {}{settings}\n", quote_ident(&field.name), dbml_type(&att.dtype))
+}
+
+/// Write objects as DBML
+///
+/// Schema's, Table's (with their ForeignKeys) and Index'es are written, other objects are skipped.
+/// The indexed fields of a Table are written as an index, read back as an Index object
+pub fn write_dbml(objects:&[&dyn DBObject]) -> String {
+    let mut s = String::new();
+    for schema in objects.iter().filter_map(|o| o.downcast_ref::<Schema>()) {
+        s += format!("{SCHEMA_COMMENT}{} owner {}\n", schema.name, schema.owner).as_str();
+    }
+    let tables:Vec<&Table> = objects.iter().filter_map(|o| o.downcast_ref::<Table>()).collect();
+    let indexes:Vec<&Index> = objects.iter().filter_map(|o| o.downcast_ref::<Index>()).collect();
+    let mut refs = Vec::new();
+    for table in tables.iter() {
+        if ! s.is_empty() {
+            s += "\n";
+        }
+        s += format!("Table {} {{\n", quote_path(&table.path)).as_str();
+        for field in table.fields.iter() {
+            s += write_field(field).as_str();
+        }
+        if let Some(comment) = &table.comment {
+            s += format!("  Note: {}\n", quote_str(comment)).as_str();
+        }
+        let mut idxs:Vec<&FieldNames> = Vec::new();
+        let flagged = table.indexes().unwrap_or_default();
+        idxs.extend(flagged.iter().map(|i| &i.fields));
+        idxs.extend(indexes.iter().filter(|i| i.table.is_equal(&table.path)).map(|i| &i.fields));
+        if ! idxs.is_empty() {
+            s += "  indexes {\n";
+            for idx in idxs {
+                s += format!("    {}\n", columns(idx)).as_str();
+            }
+            s += "  }\n";
+        }
+        s += "}\n";
+        for fk in table.foreign_keys().iter() {
+            let mut settings = Vec::new();
+            if fk.on_delete != FKOn::Restrict {
+                settings.push(format!("delete: {}", dbml_fk_on(&fk.on_delete)))
+            }
+            if fk.on_update != FKOn::Restrict {
+                settings.push(format!("update: {}", dbml_fk_on(&fk.on_update)))
+            }
+            let settings = if settings.is_empty() { "".to_owned() } else { format!(" [{}]", settings.join(", ")) };
+            refs.push(format!("Ref: {}.{} > {}.{}{settings}\n",
+                quote_path(&fk.table), columns(&fk.fields), quote_path(&fk.ref_table), columns(&fk.ref_fields)))
+        }
+    }
+    if ! refs.is_empty() {
+        s += "\n";
+        s += refs.concat().as_str();
+    }
+    s
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Expr(String),
+    Num(String),
+    Sym(String),
+}
+
+fn tokenize(data:&str) -> Result<Vec<Token>, Box<dyn Error>> {
+    let chars:Vec<char> = data.chars().collect();
+    let mut toks = Vec::new();
+    let mut i = 0;
+    let until = |from:usize, end:&str| -> Result<usize, Box<dyn Error>> {
+        let end:Vec<char> = end.chars().collect();
+        (from..chars.len()).find(|&j| chars[j..].starts_with(&end))
+            .ok_or_else(|| format!("unterminated {} in DBML", end.iter().collect::<String>()).into())
+    };
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1
+        } else if chars[i..].starts_with(&['/', '/']) {
+            i = (i..chars.len()).find(|&j| chars[j] == '\n').unwrap_or(chars.len())
+        } else if chars[i..].starts_with(&['/', '*']) {
+            i = until(i + 2, "*/")? + 2
+        } else if chars[i..].starts_with(&['\'', '\'', '\'']) {
+            let end = until(i + 3, "'''")?;
+            toks.push(Token::Str(chars[i+3..end].iter().collect()));
+            i = end + 3
+        } else if c == '\'' {
+            let mut s = String::new();
+            i += 1;
+            while i < chars.len() && chars[i] != '\'' {
+                if chars[i] == '\\' && i + 1 < chars.len() {
+                    i += 1
+                }
+                s.push(chars[i]);
+                i += 1
+            }
+            if i >= chars.len() {
+                return Err("unterminated string in DBML".into())
+            }
+            toks.push(Token::Str(s));
+            i += 1
+        } else if c == '"' || c == '`' {
+            let end = until(i + 1, &c.to_string())?;
+            let text:String = chars[i+1..end].iter().collect();
+            toks.push(if c == '"' { Token::Ident(text) } else { Token::Expr(text) });
+            i = end + 1
+        } else if c.is_ascii_digit() || (c == '-' && toks.last() == Some(&Token::Sym(":".to_owned()))) {
+            let end = (i + 1..chars.len()).find(|&j| ! (chars[j].is_ascii_digit() || chars[j] == '.')).unwrap_or(chars.len());
+            toks.push(Token::Num(chars[i..end].iter().collect()));
+            i = end
+        } else if c.is_alphanumeric() || c == '_' {
+            let end = (i..chars.len()).find(|&j| ! (chars[j].is_alphanumeric() || chars[j] == '_')).unwrap_or(chars.len());
+            toks.push(Token::Ident(chars[i..end].iter().collect()));
+            i = end
+        } else if chars[i..].starts_with(&['<', '>']) {
+            toks.push(Token::Sym("<>".to_owned()));
+            i += 2
+        } else {
+            toks.push(Token::Sym(c.to_string()));
+            i += 1
+        }
+    }
+    Ok(toks)
+}
+
+/// Setting of a table, field, index or reference: its key (lowercase words) and value tokens
+type Setting = (String, Vec<Token>);
+
+/// A reference between fields, kept until every table is read
+struct Ref {
+    from: (ObjectPath, FieldNames),
+    to: (ObjectPath, FieldNames),
+    settings: Vec<Setting>,
+}
+
+struct Parser {
+    toks: Vec<Token>,
+    pos: usize,
+    aliases: HashMap<String, ObjectPath>,
+    warnings: Vec<String>,
+}
+
+fn is_sym(tok:Option<&Token>, sym:&str) -> bool {
+    matches!(tok, Some(Token::Sym(s)) if s == sym)
+}
+
+fn is_keyword(tok:Option<&Token>, keyword:&str) -> bool {
+    matches!(tok, Some(Token::Ident(s)) if s.eq_ignore_ascii_case(keyword))
+}
+
+fn value_text(value:&[Token]) -> String {
+    let parts:Vec<String> = value.iter().map(|t| match t {
+        Token::Ident(s)|Token::Str(s)|Token::Expr(s)|Token::Num(s)|Token::Sym(s) => s.to_owned(),
+    }).collect();
+    parts.join(" ")
+}
+
+fn fk_on(value:&[Token]) -> Result<FKOn, Box<dyn Error>> {
+    Ok(match value_text(value).to_lowercase().as_str() {
+        "cascade" => FKOn::Cascade,
+        "restrict" => FKOn::Restrict,
+        "set null" => FKOn::SetNull,
+        "set default" => FKOn::SetDefault,
+        "no action" => FKOn::NoAction,
+        other => return Err(format!("unknown DBML referential action '{other}'").into()),
+    })
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.toks.get(self.pos)
+    }
+    fn peek_at(&self, offset:usize) -> Option<&Token> {
+        self.toks.get(self.pos + offset)
+    }
+    fn next(&mut self) -> Result<Token, Box<dyn Error>> {
+        let tok = self.toks.get(self.pos).cloned().ok_or("unexpected end of DBML")?;
+        self.pos += 1;
+        Ok(tok)
+    }
+    fn expect(&mut self, sym:&str) -> Result<(), Box<dyn Error>> {
+        match self.next()? {
+            Token::Sym(s) if s == sym => Ok(()),
+            other => Err(format!("expected '{sym}' in DBML, found {other:?}").into()),
+        }
+    }
+    fn name(&mut self) -> Result<String, Box<dyn Error>> {
+        match self.next()? {
+            Token::Ident(s)|Token::Str(s) => Ok(s),
+            other => Err(format!("expected a name in DBML, found {other:?}").into()),
+        }
+    }
+    /// Skip the rest of an unsupported element, up to the end of its block
+    fn skip_element(&mut self) -> Result<(), Box<dyn Error>> {
+        while ! is_sym(self.peek(), "{") {
+            self.next()?;
+        }
+        let mut depth = 0;
+        loop {
+            match self.next()? {
+                Token::Sym(s) if s == "{" => depth += 1,
+                Token::Sym(s) if s == "}" => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(())
+                    }
+                },
+                _ => (),
+            }
+        }
+    }
+    /// Settings between [ and ], if present
+    fn settings(&mut self) -> Result<Vec<Setting>, Box<dyn Error>> {
+        let mut settings = Vec::new();
+        if ! is_sym(self.peek(), "[") {
+            return Ok(settings)
+        }
+        self.next()?;
+        let mut current:Vec<Token> = Vec::new();
+        let mut depth = 0;
+        loop {
+            let tok = self.next()?;
+            match &tok {
+                Token::Sym(s) if depth == 0 && (s == "," || s == "]") => {
+                    if ! current.is_empty() {
+                        let colon = current.iter().position(|t| *t == Token::Sym(":".to_owned()));
+                        let (key, value) = match colon {
+                            Some(pos) => (current[..pos].to_vec(), current[pos+1..].to_vec()),
+                            None => (current.to_vec(), Vec::new()),
+                        };
+                        settings.push((value_text(&key).to_lowercase(), value));
+                        current.clear();
+                    }
+                    if s == "]" {
+                        return Ok(settings)
+                    }
+                },
+                Token::Sym(s) => {
+                    if s == "(" { depth += 1 }
+                    if s == ")" { depth -= 1 }
+                    current.push(tok)
+                },
+                _ => current.push(tok),
+            }
+        }
+    }
+    /// Table path from its name parts, resolving aliases
+    fn table_path(&self, parts:&[String]) -> ObjectPath {
+        if parts.len() == 1 {
+            if let Some(path) = self.aliases.get(&parts[0]) {
+                return path.to_owned()
+            }
+        }
+        ObjectPath::new_table_from_full_name(&parts.join("."))
+    }
+    /// Table and fields of a reference endpoint: table.field, schema.table.field or table.(f1, f2)
+    fn endpoint(&mut self) -> Result<(ObjectPath, FieldNames), Box<dyn Error>> {
+        let mut parts = vec![self.name()?];
+        let mut fields = Vec::new();
+        while is_sym(self.peek(), ".") {
+            self.next()?;
+            if is_sym(self.peek(), "(") {
+                self.next()?;
+                while ! is_sym(self.peek(), ")") {
+                    fields.push(self.name()?);
+                    if is_sym(self.peek(), ",") {
+                        self.next()?;
+                    }
+                }
+                self.next()?;
+                break
+            }
+            parts.push(self.name()?)
+        }
+        if fields.is_empty() {
+            fields.push(parts.pop().unwrap_or_default())
+        }
+        if parts.is_empty() {
+            return Err(format!("reference to {fields:?} without table in DBML").into())
+        }
+        Ok((self.table_path(&parts), fields))
+    }
+    /// Relationship after its left endpoint: the operator and the right endpoint
+    fn relation(&mut self, left:(ObjectPath, FieldNames)) -> Result<Option<Ref>, Box<dyn Error>> {
+        let op = match self.next()? {
+            Token::Sym(s) if ["<", ">", "-", "<>"].contains(&s.as_str()) => s,
+            other => return Err(format!("expected a relationship in DBML, found {other:?}").into()),
+        };
+        let right = self.endpoint()?;
+        let settings = self.settings()?;
+        Ok(match op.as_str() {
+            "<" => Some(Ref { from: right, to: left, settings }),
+            "<>" => {
+                self.warnings.push(format!("many-to-many reference {}.{:?} <> {}.{:?} skipped",
+                    left.0.full_name(), left.1, right.0.full_name(), right.1));
+                None
+            },
+            _ => Some(Ref { from: left, to: right, settings }),
+        })
+    }
+    fn parse_ref(&mut self, refs:&mut Vec<Ref>) -> Result<(), Box<dyn Error>> {
+        while ! is_sym(self.peek(), ":") && ! is_sym(self.peek(), "{") {
+            self.next()?;
+        }
+        if is_sym(self.peek(), ":") {
+            self.next()?;
+            let left = self.endpoint()?;
+            refs.extend(self.relation(left)?);
+        } else {
+            self.next()?;
+            while ! is_sym(self.peek(), "}") {
+                let left = self.endpoint()?;
+                refs.extend(self.relation(left)?);
+            }
+            self.next()?;
+        }
+        Ok(())
+    }
+    fn parse_field(&mut self, path:&ObjectPath, refs:&mut Vec<Ref>) -> Result<Field, Box<dyn Error>> {
+        let name = self.name()?;
+        let dtype = self.name()?;
+        if is_sym(self.peek(), "(") {
+            while ! is_sym(self.peek(), ")") {
+                self.next()?;
+            }
+            self.next()?;
+        }
+        let mut attrs = FieldAttributes::new(FieldType::from_sql_type(&dtype).unwrap_or_else(|| {
+            self.warnings.push(format!("unknown type '{dtype}' of {}.{name} read as text", path.full_name()));
+            FieldType::Txt
+        }));
+        for (key, value) in self.settings()? {
+            match key.as_str() {
+                "pk"|"primary key" => {
+                    attrs.primary_key = true;
+                    attrs.empty = false
+                },
+                "not null" => attrs.empty = false,
+                "null" => attrs.empty = true,
+                "unique" => attrs.unique = true,
+                "increment" => attrs.dtype = FieldType::AutoInc,
+                "default" => attrs.defval = Some(value_text(&value)),
+                "note" => attrs.comment = Some(value_text(&value)),
+                "ref" => {
+                    let mut sub = Parser { toks: value, pos: 0, aliases: self.aliases.to_owned(), warnings: Vec::new() };
+                    let rel = sub.relation((path.to_owned(), vec![name.to_owned()]))?;
+                    self.warnings.append(&mut sub.warnings);
+                    refs.extend(rel)
+                },
+                other => self.warnings.push(format!("setting '{other}' of {}.{name} skipped", path.full_name())),
+            }
+        }
+        Ok(Field::new(&name, &attrs))
+    }
+    fn parse_indexes(&mut self, path:&ObjectPath, fields:&mut Fields) -> Result<Vec<Index>, Box<dyn Error>> {
+        let mut indexes = Vec::new();
+        self.expect("{")?;
+        while ! is_sym(self.peek(), "}") {
+            let mut names = Vec::new();
+            match self.next()? {
+                Token::Sym(s) if s == "(" => {
+                    while ! is_sym(self.peek(), ")") {
+                        match self.next()? {
+                            Token::Ident(n) => names.push(n),
+                            Token::Sym(s) if s == "," => (),
+                            other => self.warnings.push(format!("index element {other:?} of {} skipped", path.full_name())),
+                        }
+                    }
+                    self.next()?;
+                },
+                Token::Ident(n) => names.push(n),
+                other => self.warnings.push(format!("index {other:?} of {} skipped", path.full_name())),
+            }
+            let settings = self.settings()?;
+            if names.is_empty() {
+                continue
+            }
+            let has = |key:&str| settings.iter().any(|(k, _)| k == key);
+            if has("pk") {
+                for f in fields.iter_mut().filter(|f| names.contains(&f.name)) {
+                    f.attributes.primary_key = true;
+                    f.attributes.empty = false;
+                }
+            } else if has("unique") && names.len() == 1 {
+                for f in fields.iter_mut().filter(|f| names.contains(&f.name)) {
+                    f.attributes.unique = true
+                }
+            } else {
+                if has("unique") {
+                    self.warnings.push(format!("unique index {names:?} of {} read as a non unique index", path.full_name()))
+                }
+                indexes.push(Index::new(path, &names))
+            }
+        }
+        self.next()?;
+        Ok(indexes)
+    }
+    fn parse_table(&mut self, refs:&mut Vec<Ref>) -> Result<(Table, Vec<Index>), Box<dyn Error>> {
+        let mut parts = vec![self.name()?];
+        while is_sym(self.peek(), ".") {
+            self.next()?;
+            parts.push(self.name()?)
+        }
+        let path = ObjectPath::new_table_from_full_name(&parts.join("."));
+        if is_keyword(self.peek(), "as") {
+            self.next()?;
+            let alias = self.name()?;
+            self.aliases.insert(alias, path.to_owned());
+        }
+        let mut comment = None;
+        for (key, value) in self.settings()? {
+            if key == "note" {
+                comment = Some(value_text(&value))
+            }
+        }
+        self.expect("{")?;
+        let mut fields = Fields::new();
+        let mut indexes = Vec::new();
+        while ! is_sym(self.peek(), "}") {
+            if is_keyword(self.peek(), "note") && is_sym(self.peek_at(1), ":") {
+                self.pos += 2;
+                comment = Some(value_text(&[self.next()?]))
+            } else if is_keyword(self.peek(), "note") && is_sym(self.peek_at(1), "{") {
+                self.pos += 2;
+                comment = Some(value_text(&[self.next()?]));
+                self.expect("}")?
+            } else if is_keyword(self.peek(), "indexes") && is_sym(self.peek_at(1), "{") {
+                self.next()?;
+                indexes = self.parse_indexes(&path, &mut fields)?
+            } else {
+                fields.push(self.parse_field(&path, refs)?)
+            }
+        }
+        self.next()?;
+        let mut names = HashSet::new();
+        if let Some(dup) = fields.iter().find(|f| ! names.insert(&f.name)) {
+            return Err(format!("{} has duplicated field {}", path.full_name(), dup.name).into())
+        }
+        let mut table = Table::new(&path, fields, None);
+        table.comment = comment;
+        Ok((table, indexes))
+    }
+}
+
+/// Read DBML into DBObject's, returning them with the warnings about the elements that were skipped
+///
+/// Tables (with their references as ForeignKeys), indexes and "// schema <name> owner <owner>"
+/// comments (as Schema's) are read; enums, table groups and project definitions are skipped
+pub fn read_dbml(data:&str) -> Result<(Objects, Vec<String>), Box<dyn Error>> {
+    let mut objs = Objects::new();
+    for line in data.lines() {
+        if let Some(rest) = line.trim_start().strip_prefix(SCHEMA_COMMENT) {
+            let words:Vec<&str> = rest.split_whitespace().collect();
+            if let [name, "owner", owner] = words.as_slice() {
+                objs.push(Box::new(Schema::new(name, owner)))
+            }
+        }
+    }
+    let mut parser = Parser { toks: tokenize(data)?, pos: 0, aliases: HashMap::new(), warnings: Vec::new() };
+    let mut tables:Vec<(Table, Vec<Index>)> = Vec::new();
+    let mut refs = Vec::new();
+    while let Some(tok) = parser.peek().cloned() {
+        if is_keyword(Some(&tok), "table") {
+            parser.next()?;
+            tables.push(parser.parse_table(&mut refs)?)
+        } else if is_keyword(Some(&tok), "ref") {
+            parser.next()?;
+            parser.parse_ref(&mut refs)?
+        } else {
+            parser.warnings.push(format!("DBML element {} skipped", value_text(&[tok])));
+            parser.skip_element()?
+        }
+    }
+    for rel in refs {
+        let (table, _) = match tables.iter_mut().find(|(t, _)| t.path.is_equal(&rel.from.0)) {
+            Some(found) => found,
+            None => {
+                parser.warnings.push(format!("reference from unknown table {} skipped", rel.from.0.full_name()));
+                continue
+            },
+        };
+        let mut fk = ForeignKey::new(&table.path, &rel.from.1, &rel.to.0, &rel.to.1);
+        for (key, value) in rel.settings.iter() {
+            match key.as_str() {
+                "delete" => fk.on_delete = fk_on(value)?,
+                "update" => fk.on_update = fk_on(value)?,
+                other => parser.warnings.push(format!("setting '{other}' of {} skipped", fk.name())),
+            }
+        }
+        if fk.sets_null() && table.fields.iter().any(|f| fk.fields.contains(&f.name) && ! f.attributes.empty) {
+            parser.warnings.push(format!("{} uses SET NULL on NOT NULL fields, skipped", fk.name()));
+            continue
+        }
+        table.fks.get_or_insert_with(Vec::new).push(fk)
+    }
+    for (table, indexes) in tables {
+        objs.push(Box::new(table));
+        for index in indexes {
+            objs.push(Box::new(index))
+        }
+    }
+    Ok((objs, parser.warnings))
+}
@@ -0,0 +1,790 @@
+use crate::sql::*;
+use std::collections::HashSet;
+use std::error::Error;
+
+type Objects = Vec<Box<dyn DBObject>>;
+
+/// Split SQL into statements, honoring quotes, comments, dollar quoting and MySQL's DELIMITER
+fn split_statements(data:&str) -> Vec<String> {
+    let chars:Vec<char> = data.chars().collect();
+    let mut stmts = Vec::new();
+    let mut cur = String::new();
+    let mut delimiter:Vec<char> = vec![';'];
+    let mut i = 0;
+    let find = |from:usize, end:&[char]| (from..chars.len()).find(|&j| chars[j..].starts_with(end)).unwrap_or(chars.len());
+    while i < chars.len() {
+        let c = chars[i];
+        if cur.trim().is_empty() && chars[i..].iter().take(10).collect::<String>().eq_ignore_ascii_case("delimiter ") {
+            let eol = find(i, &['\n']);
+            delimiter = chars[i+10..eol].iter().collect::<String>().trim().chars().collect();
+            cur.clear();
+            i = eol
+        } else if chars[i..].starts_with(&['-', '-']) {
+            i = find(i, &['\n'])
+        } else if chars[i..].starts_with(&['/', '*']) {
+            i = (find(i + 2, &['*', '/']) + 2).min(chars.len())
+        } else if c == '\'' || c == '"' || c == '`' {
+            let mut j = i + 1;
+            while j < chars.len() {
+                if chars[j] == '\\' && c == '\'' {
+                    j += 1
+                } else if chars[j] == c {
+                    if j + 1 < chars.len() && chars[j + 1] == c {
+                        j += 1
+                    } else {
+                        break
+                    }
+                }
+                j += 1
+            }
+            let end = (j + 1).min(chars.len());
+            cur.extend(&chars[i..end]);
+            i = end
+        } else if c == '$' && (i + 1..chars.len()).find(|&j| ! (chars[j].is_alphanumeric() || chars[j] == '_')).map_or(false, |j| chars[j] == '$') {
+            let tag_end = (i + 1..chars.len()).find(|&j| chars[j] == '$').unwrap_or(i);
+            let tag:Vec<char> = chars[i..=tag_end].to_vec();
+            let end = (find(tag_end + 1, &tag) + tag.len()).min(chars.len());
+            cur.extend(&chars[i..end]);
+            i = end
+        } else if ! delimiter.is_empty() && chars[i..].starts_with(&delimiter) {
+            if ! cur.trim().is_empty() {
+                stmts.push(cur.trim().to_owned())
+            }
+            cur.clear();
+            i += delimiter.len()
+        } else {
+            cur.push(c);
+            i += 1
+        }
+    }
+    if ! cur.trim().is_empty() {
+        stmts.push(cur.trim().to_owned())
+    }
+    stmts
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Unquoted identifier or keyword
+    Word(String),
+    /// Quoted identifier
+    Quoted(String),
+    Str(String),
+    Num(String),
+    Sym(char),
+}
+
+fn tokenize(stmt:&str) -> Vec<Token> {
+    let chars:Vec<char> = stmt.chars().collect();
+    let mut toks = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1
+        } else if c == '\'' || c == '"' || c == '`' {
+            let mut s = String::new();
+            let mut j = i + 1;
+            while j < chars.len() {
+                if chars[j] == '\\' && c == '\'' && j + 1 < chars.len() {
+                    j += 1
+                } else if chars[j] == c {
+                    if j + 1 < chars.len() && chars[j + 1] == c {
+                        j += 1
+                    } else {
+                        break
+                    }
+                }
+                s.push(chars[j]);
+                j += 1
+            }
+            toks.push(if c == '\'' { Token::Str(s) } else { Token::Quoted(s) });
+            i = j + 1
+        } else if c == '$' && (i + 1..chars.len()).find(|&j| ! (chars[j].is_alphanumeric() || chars[j] == '_')).map_or(false, |j| chars[j] == '$') {
+            let tag_end = (i + 1..chars.len()).find(|&j| chars[j] == '$').unwrap_or(i);
+            let tag:Vec<char> = chars[i..=tag_end].to_vec();
+            let end = (tag_end + 1..chars.len()).find(|&j| chars[j..].starts_with(&tag)).unwrap_or(chars.len());
+            toks.push(Token::Str(chars[tag_end + 1..end].iter().collect()));
+            i = end + tag.len()
+        } else if c.is_ascii_digit() {
+            let end = (i..chars.len()).find(|&j| ! (chars[j].is_ascii_digit() || chars[j] == '.')).unwrap_or(chars.len());
+            toks.push(Token::Num(chars[i..end].iter().collect()));
+            i = end
+        } else if c.is_alphanumeric() || c == '_' {
+            let end = (i..chars.len()).find(|&j| ! (chars[j].is_alphanumeric() || chars[j] == '_' || chars[j] == '$')).unwrap_or(chars.len());
+            toks.push(Token::Word(chars[i..end].iter().collect()));
+            i = end
+        } else {
+            toks.push(Token::Sym(c));
+            i += 1
+        }
+    }
+    toks
+}
+
+/// Write tokens back as SQL text
+fn tokens_text(toks:&[Token]) -> String {
+    let mut s = String::new();
+    let mut last_word = false;
+    for tok in toks.iter() {
+        let (text, word) = match tok {
+            Token::Word(w)|Token::Num(w) => (w.to_owned(), true),
+            Token::Quoted(q) => (format!("\"{q}\""), true),
+            Token::Str(v) => (format!("'{}'", v.replace('\'', "''")), true),
+            Token::Sym(c) => (c.to_string(), false),
+        };
+        if word && last_word {
+            s.push(' ')
+        }
+        s += text.as_str();
+        last_word = word;
+    }
+    s
+}
+
+/// Words starting a column constraint
+const COLUMN_CLAUSES: [&str; 16] = ["NOT", "NULL", "DEFAULT", "PRIMARY", "UNIQUE", "REFERENCES", "CONSTRAINT", "CHECK",
+    "COLLATE", "GENERATED", "AS", "AUTO_INCREMENT", "AUTOINCREMENT", "COMMENT", "ON", "IDENTITY"];
+
+/// Column and table constraints of a CREATE TABLE being read
+struct TableDef {
+    path: ObjectPath,
+    fields: Fields,
+    fks: ForeignKeys,
+    unique_groups: Vec<FieldNames>,
+    indexes: Vec<Index>,
+    comment: Option<String>,
+}
+
+struct Parser<'a> {
+    toks: Vec<Token>,
+    pos: usize,
+    warnings: &'a mut Vec<String>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.toks.get(self.pos)
+    }
+    fn next(&mut self) -> Result<Token, Box<dyn Error>> {
+        let tok = self.toks.get(self.pos).cloned().ok_or("unexpected end of statement")?;
+        self.pos += 1;
+        Ok(tok)
+    }
+    fn is_kw(&self, kw:&str) -> bool {
+        matches!(self.peek(), Some(Token::Word(w)) if w.eq_ignore_ascii_case(kw))
+    }
+    fn is_any_kw(&self, kws:&[&str]) -> bool {
+        kws.iter().any(|kw| self.is_kw(kw))
+    }
+    fn is_sym(&self, sym:char) -> bool {
+        self.peek() == Some(&Token::Sym(sym))
+    }
+    fn eat_kw(&mut self, kw:&str) -> bool {
+        let found = self.is_kw(kw);
+        if found {
+            self.pos += 1
+        }
+        found
+    }
+    fn eat_sym(&mut self, sym:char) -> bool {
+        let found = self.is_sym(sym);
+        if found {
+            self.pos += 1
+        }
+        found
+    }
+    fn expect_kw(&mut self, kw:&str) -> Result<(), Box<dyn Error>> {
+        if self.eat_kw(kw) {
+            Ok(())
+        } else {
+            Err(format!("expected {kw}, found {:?}", self.peek()).into())
+        }
+    }
+    fn expect_sym(&mut self, sym:char) -> Result<(), Box<dyn Error>> {
+        if self.eat_sym(sym) {
+            Ok(())
+        } else {
+            Err(format!("expected '{sym}', found {:?}", self.peek()).into())
+        }
+    }
+    fn name(&mut self) -> Result<String, Box<dyn Error>> {
+        match self.next()? {
+            Token::Word(s)|Token::Quoted(s) => Ok(s),
+            other => Err(format!("expected a name, found {other:?}").into()),
+        }
+    }
+    fn string(&mut self) -> Result<String, Box<dyn Error>> {
+        match self.next()? {
+            Token::Str(s) => Ok(s),
+            other => Err(format!("expected a string, found {other:?}").into()),
+        }
+    }
+    /// Dotted name, as its parts
+    fn dotted(&mut self) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut parts = vec![self.name()?];
+        while self.eat_sym('.') {
+            parts.push(self.name()?)
+        }
+        Ok(parts)
+    }
+    fn path(&mut self, otype:ObjectType) -> Result<ObjectPath, Box<dyn Error>> {
+        let parts = self.dotted()?;
+        let (schema, name) = match parts.as_slice() {
+            [name] => (None, name.to_owned()),
+            [schema, name] => (Some(schema.to_owned()), name.to_owned()),
+            _ => return Err(format!("unsupported name {}", parts.join(".")).into()),
+        };
+        Ok(ObjectPath { schema, name, otype })
+    }
+    fn skip_if_not_exists(&mut self) {
+        if self.is_kw("IF") {
+            self.pos += 3
+        }
+    }
+    /// Tokens of a parenthesized group, without the parenthesis
+    fn group(&mut self) -> Result<Vec<Token>, Box<dyn Error>> {
+        self.expect_sym('(')?;
+        let mut depth = 1;
+        let mut toks = Vec::new();
+        loop {
+            let tok = self.next()?;
+            match tok {
+                Token::Sym('(') => depth += 1,
+                Token::Sym(')') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(toks)
+                    }
+                },
+                _ => (),
+            }
+            toks.push(tok)
+        }
+    }
+    /// Names of a parenthesized list of columns
+    fn columns(&mut self) -> Result<FieldNames, Box<dyn Error>> {
+        let mut names = Vec::new();
+        for tok in self.group()? {
+            match tok {
+                Token::Word(n)|Token::Quoted(n) if ! ["ASC", "DESC"].iter().any(|k| n.eq_ignore_ascii_case(k)) => names.push(n),
+                _ => (),
+            }
+        }
+        Ok(names)
+    }
+    /// Tokens up to the next ',' or ')' not nested in parenthesis (left unread)
+    fn until_item_end(&mut self, stop:&[&str]) -> Vec<Token> {
+        let mut depth = 0;
+        let mut toks = Vec::new();
+        while let Some(tok) = self.peek() {
+            match tok {
+                Token::Sym(',')|Token::Sym(')') if depth == 0 => break,
+                Token::Word(_) if depth == 0 && self.is_any_kw(stop) => break,
+                Token::Sym('(') => depth += 1,
+                Token::Sym(')') => depth -= 1,
+                _ => (),
+            }
+            toks.push(tok.to_owned());
+            self.pos += 1
+        }
+        toks
+    }
+    fn fk_on(&mut self) -> Result<FKOn, Box<dyn Error>> {
+        Ok(if self.eat_kw("CASCADE") {
+            FKOn::Cascade
+        } else if self.eat_kw("RESTRICT") {
+            FKOn::Restrict
+        } else if self.eat_kw("SET") {
+            if self.eat_kw("NULL") { FKOn::SetNull } else { self.expect_kw("DEFAULT")?; FKOn::SetDefault }
+        } else {
+            self.expect_kw("NO")?;
+            self.expect_kw("ACTION")?;
+            FKOn::NoAction
+        })
+    }
+    /// REFERENCES clause, after the REFERENCES keyword
+    fn references(&mut self, table:&ObjectPath, fields:FieldNames) -> Result<ForeignKey, Box<dyn Error>> {
+        let ref_table = self.path(ObjectType::Table)?;
+        let ref_fields = if self.is_sym('(') { self.columns()? } else { Vec::new() };
+        if ref_fields.is_empty() {
+            return Err(format!("reference from {}.{fields:?} to {} without columns", table.full_name(), ref_table.full_name()).into())
+        }
+        let mut fk = ForeignKey::new(table, &fields, &ref_table, &ref_fields);
+        loop {
+            if self.eat_kw("ON") {
+                if self.eat_kw("DELETE") {
+                    fk.on_delete = self.fk_on()?
+                } else {
+                    self.expect_kw("UPDATE")?;
+                    fk.on_update = self.fk_on()?
+                }
+            } else if self.eat_kw("MATCH") {
+                fk.match_type = if self.eat_kw("FULL") {
+                    Some(FKMatch::Full)
+                } else {
+                    let kind = self.name()?;
+                    if ! kind.eq_ignore_ascii_case("SIMPLE") {
+                        self.warnings.push(format!("MATCH {kind} of {} read as MATCH SIMPLE", fk.name()))
+                    }
+                    Some(FKMatch::Simple)
+                }
+            } else if self.eat_kw("DEFERRABLE") {
+                fk.deferrable = true
+            } else if self.eat_kw("NOT") {
+                self.expect_kw("DEFERRABLE")?
+            } else if self.eat_kw("INITIALLY") {
+                fk.initially_deferred = self.eat_kw("DEFERRED");
+                self.eat_kw("IMMEDIATE");
+            } else {
+                return Ok(fk)
+            }
+        }
+    }
+    fn column(&mut self, def:&mut TableDef) -> Result<(), Box<dyn Error>> {
+        let name = self.name()?;
+        let where_ = format!("{}.{name}", def.path.full_name());
+        let mut words = Vec::new();
+        let mut dtype_name = String::new();
+        while let Some(tok) = self.peek() {
+            match tok {
+                Token::Sym(',')|Token::Sym(')') => break,
+                Token::Word(_) if self.is_any_kw(&COLUMN_CLAUSES) => break,
+                Token::Sym('(') => {
+                    let args = tokens_text(&self.group()?);
+                    dtype_name = format!("{}({args})", words.join(" "))
+                },
+                Token::Word(w)|Token::Quoted(w) => {
+                    words.push(w.to_owned());
+                    dtype_name = words.join(" ");
+                    self.pos += 1
+                },
+                _ => return Err(format!("unexpected {tok:?} in the type of {where_}").into()),
+            }
+        }
+        let dtype = match FieldType::from_sql_type(&dtype_name) {
+            Some(dtype) => dtype,
+            None => {
+                let prefix = (1..words.len()).rev().find_map(|n| FieldType::from_sql_type(&words[..n].join(" ")));
+                let dtype = prefix.unwrap_or(FieldType::Txt);
+                self.warnings.push(format!("type '{dtype_name}' of {where_} read as {dtype:?}"));
+                dtype
+            },
+        };
+        let mut attrs = FieldAttributes::new(dtype);
+        loop {
+            if self.is_sym(',') || self.is_sym(')') || self.peek().is_none() {
+                break
+            } else if self.eat_kw("CONSTRAINT") {
+                self.name()?;
+            } else if self.eat_kw("NOT") {
+                self.expect_kw("NULL")?;
+                attrs.empty = false
+            } else if self.eat_kw("NULL") {
+                attrs.empty = true
+            } else if self.eat_kw("DEFAULT") {
+                let value = self.until_item_end(&COLUMN_CLAUSES);
+                attrs.defval = Some(match value.as_slice() {
+                    [Token::Str(s)]|[Token::Quoted(s)] => s.to_owned(),
+                    _ => tokens_text(&value),
+                })
+            } else if self.eat_kw("PRIMARY") {
+                self.expect_kw("KEY")?;
+                attrs.primary_key = true;
+                attrs.empty = false;
+                if ! self.eat_kw("ASC") {
+                    self.eat_kw("DESC");
+                }
+            } else if self.eat_kw("AUTO_INCREMENT") || self.eat_kw("AUTOINCREMENT") {
+                attrs.dtype = FieldType::AutoInc
+            } else if self.eat_kw("UNIQUE") {
+                self.eat_kw("KEY");
+                attrs.unique = true;
+                def.unique_groups.push(vec![name.to_owned()])
+            } else if self.eat_kw("REFERENCES") {
+                let fk = self.references(&def.path, vec![name.to_owned()])?;
+                def.fks.push(fk)
+            } else if self.eat_kw("GENERATED") {
+                if self.eat_kw("BY") {
+                    self.expect_kw("DEFAULT")?;
+                    self.identity(&mut attrs, IdentityGeneration::ByDefault)?
+                } else {
+                    self.expect_kw("ALWAYS")?;
+                    if self.is_kw("AS") && matches!(self.toks.get(self.pos + 1), Some(Token::Word(w)) if w.eq_ignore_ascii_case("IDENTITY")) {
+                        self.identity(&mut attrs, IdentityGeneration::Always)?
+                    } else {
+                        self.generated(&mut attrs)?
+                    }
+                }
+            } else if self.is_kw("AS") {
+                self.generated(&mut attrs)?
+            } else if self.eat_kw("COMMENT") {
+                attrs.comment = Some(self.string()?)
+            } else {
+                let mut clause = vec![self.next()?];
+                clause.append(&mut self.until_item_end(&COLUMN_CLAUSES));
+                self.warnings.push(format!("clause '{}' of {where_} skipped", tokens_text(&clause)))
+            }
+        }
+        def.fields.push(Field::new(&name, &attrs));
+        Ok(())
+    }
+    fn identity(&mut self, attrs:&mut FieldAttributes, generated:IdentityGeneration) -> Result<(), Box<dyn Error>> {
+        self.expect_kw("AS")?;
+        self.expect_kw("IDENTITY")?;
+        if self.is_sym('(') {
+            self.group()?;
+        }
+        let width = if attrs.dtype == FieldType::BigInt { IdentityWidth::BigInt } else { IdentityWidth::Int };
+        attrs.dtype = FieldType::AutoInc;
+        attrs.identity = Some(Identity::new(generated, width));
+        Ok(())
+    }
+    fn generated(&mut self, attrs:&mut FieldAttributes) -> Result<(), Box<dyn Error>> {
+        self.expect_kw("AS")?;
+        let expression = tokens_text(&self.group()?);
+        let storage = if self.eat_kw("VIRTUAL") {
+            GeneratedStorage::Virtual
+        } else {
+            self.eat_kw("STORED");
+            GeneratedStorage::Stored
+        };
+        attrs.generated = Some(Generated { expression, storage });
+        Ok(())
+    }
+    /// Table constraint (or MySQL index), returns false if the item is a column
+    fn table_constraint(&mut self, def:&mut TableDef) -> Result<bool, Box<dyn Error>> {
+        if self.eat_kw("CONSTRAINT") {
+            self.name()?;
+        } else if ! self.is_any_kw(&["PRIMARY", "UNIQUE", "FOREIGN", "CHECK", "KEY", "INDEX"]) {
+            return Ok(false)
+        }
+        if self.eat_kw("PRIMARY") {
+            self.expect_kw("KEY")?;
+            let names = self.columns()?;
+            for f in def.fields.iter_mut().filter(|f| names.contains(&f.name)) {
+                f.attributes.primary_key = true;
+                f.attributes.empty = false;
+            }
+        } else if self.eat_kw("UNIQUE") {
+            if ! self.eat_kw("KEY") {
+                self.eat_kw("INDEX");
+            }
+            if ! self.is_sym('(') {
+                self.name()?;
+            }
+            let names = self.columns()?;
+            for f in def.fields.iter_mut().filter(|f| names.contains(&f.name)) {
+                f.attributes.unique = true
+            }
+            def.unique_groups.push(names)
+        } else if self.eat_kw("FOREIGN") {
+            self.expect_kw("KEY")?;
+            let names = self.columns()?;
+            self.expect_kw("REFERENCES")?;
+            let fk = self.references(&def.path, names)?;
+            def.fks.push(fk)
+        } else if self.eat_kw("KEY") || self.eat_kw("INDEX") {
+            if ! self.is_sym('(') {
+                self.name()?;
+            }
+            let names = self.columns()?;
+            def.indexes.push(Index::new(&def.path, &names))
+        } else {
+            let clause = tokens_text(&self.until_item_end(&[]));
+            self.warnings.push(format!("constraint '{clause}' of {} skipped", def.path.full_name()))
+        }
+        Ok(true)
+    }
+    fn create_table(&mut self, objs:&mut Objects) -> Result<(), Box<dyn Error>> {
+        self.skip_if_not_exists();
+        let path = self.path(ObjectType::Table)?;
+        let mut def = TableDef { path, fields: Vec::new(), fks: Vec::new(), unique_groups: Vec::new(), indexes: Vec::new(), comment: None };
+        self.expect_sym('(')?;
+        loop {
+            if ! self.table_constraint(&mut def)? {
+                self.column(&mut def)?
+            }
+            if ! self.eat_sym(',') {
+                break
+            }
+        }
+        self.expect_sym(')')?;
+        let mut options = Vec::new();
+        while self.peek().is_some() {
+            if self.eat_kw("COMMENT") {
+                self.eat_sym('=');
+                def.comment = Some(self.string()?)
+            } else {
+                options.push(self.next()?)
+            }
+        }
+        let where_ = def.path.full_name();
+        if ! options.is_empty() {
+            self.warnings.push(format!("table options '{}' of {where_} skipped", tokens_text(&options)))
+        }
+        if def.unique_groups.len() > 1 {
+            self.warnings.push(format!("UNIQUE constraints {:?} of {where_} merged into one", def.unique_groups))
+        }
+        let mut names = HashSet::new();
+        if let Some(dup) = def.fields.iter().find(|f| ! names.insert(&f.name)) {
+            return Err(format!("{where_} has duplicated field {}", dup.name).into())
+        }
+        let fields = &def.fields;
+        let (fks, invalid):(ForeignKeys, ForeignKeys) = def.fks.into_iter().partition(|fk| {
+            ! fk.sets_null() || fields.iter().all(|f| ! fk.fields.contains(&f.name) || f.attributes.empty)
+        });
+        for fk in invalid.iter() {
+            self.warnings.push(format!("{} uses SET NULL on NOT NULL fields, skipped", fk.name()))
+        }
+        let mut table = Table::new(&def.path, def.fields, if fks.is_empty() { None } else { Some(fks) });
+        table.comment = def.comment;
+        objs.push(Box::new(table));
+        for index in def.indexes {
+            objs.push(Box::new(index))
+        }
+        Ok(())
+    }
+    fn create_index(&mut self, objs:&mut Objects, unique:bool) -> Result<(), Box<dyn Error>> {
+        self.skip_if_not_exists();
+        let name = if self.is_kw("ON") { "".to_owned() } else { self.name()? };
+        self.expect_kw("ON")?;
+        let table = self.path(ObjectType::Table)?;
+        if self.eat_kw("USING") {
+            self.name()?;
+        }
+        let index = Index::new(&table, &self.columns()?);
+        if unique {
+            self.warnings.push(format!("unique index {name} read as the non unique index {}", index.name()))
+        }
+        if self.peek().is_some() {
+            self.warnings.push(format!("clauses '{}' of index {name} skipped", tokens_text(&self.toks[self.pos..])))
+        }
+        objs.push(Box::new(index));
+        Ok(())
+    }
+    /// Type of object after ON in GRANT or ALTER, Table if not present
+    fn object_type(&mut self) -> ObjectType {
+        for (kw, otype) in [("TABLE", ObjectType::Table), ("SCHEMA", ObjectType::Schema), ("SEQUENCE", ObjectType::Sequence),
+                ("VIEW", ObjectType::View), ("FUNCTION", ObjectType::Function), ("PROCEDURE", ObjectType::Procedure)] {
+            if self.eat_kw(kw) {
+                return otype
+            }
+        }
+        if self.is_kw("MATERIALIZED") {
+            self.pos += 2;
+            return ObjectType::MaterializedView
+        }
+        ObjectType::Table
+    }
+    fn object_path(&mut self, otype:ObjectType) -> Result<ObjectPath, Box<dyn Error>> {
+        let path = self.path(otype)?;
+        if self.is_sym('(') {
+            self.group()?;
+        }
+        Ok(path)
+    }
+    /// Privileges of GRANT and REVOKE with their columns, up to ON
+    fn privileges(&mut self) -> Result<(Vec<GrantType>, FieldNames), Box<dyn Error>> {
+        let mut permissions = Vec::new();
+        let mut columns = Vec::new();
+        while ! self.is_kw("ON") {
+            let word = self.name()?;
+            let permission = match word.to_uppercase().as_str() {
+                "SELECT" => GrantType::Select,
+                "INSERT" => GrantType::Insert,
+                "UPDATE" => GrantType::Update,
+                "DELETE" => GrantType::Delete,
+                "TRUNCATE" => GrantType::Truncate,
+                "REFERENCES" => GrantType::References,
+                "TRIGGER" => GrantType::Trigger,
+                "ALL" => {
+                    self.eat_kw("PRIVILEGES");
+                    GrantType::All
+                },
+                "USAGE" => GrantType::Usage,
+                "EXECUTE" => GrantType::Execute,
+                _ => return Err(format!("unsupported privilege {word}").into()),
+            };
+            if self.is_sym('(') {
+                let cols = self.columns()?;
+                if ! columns.is_empty() && columns != cols {
+                    return Err("privileges on different columns are not supported".into())
+                }
+                columns = cols;
+            }
+            permissions.push(permission);
+            self.eat_sym(',');
+        }
+        self.expect_kw("ON")?;
+        Ok((permissions, columns))
+    }
+    /// Object after ON in GRANT and REVOKE, with whether it is ALL TABLES IN SCHEMA
+    fn privileges_target(&mut self) -> Result<(ObjectPath, bool), Box<dyn Error>> {
+        if self.eat_kw("ALL") {
+            if ! self.eat_kw("TABLES") {
+                return Err("GRANT ON ALL objects IN SCHEMA other than TABLES is not supported".into())
+            }
+            self.expect_kw("IN")?;
+            self.expect_kw("SCHEMA")?;
+            return Ok((ObjectPath::new_table_only(&self.name()?), true))
+        }
+        let otype = self.object_type();
+        Ok((self.object_path(otype)?, false))
+    }
+    fn roles(&mut self) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut roles = vec![self.name()?];
+        while self.eat_sym(',') {
+            roles.push(self.name()?)
+        }
+        Ok(roles)
+    }
+    fn granted_by(&mut self) -> Result<Option<String>, Box<dyn Error>> {
+        if self.eat_kw("GRANTED") {
+            self.expect_kw("BY")?;
+            return Ok(Some(self.name()?))
+        }
+        Ok(None)
+    }
+    fn grant(&mut self, objs:&mut Objects) -> Result<(), Box<dyn Error>> {
+        let (permissions, columns) = self.privileges()?;
+        let (on, all_tables) = self.privileges_target()?;
+        self.expect_kw("TO")?;
+        let roles = self.roles()?;
+        let with_grant_option = self.eat_kw("WITH");
+        if with_grant_option {
+            self.expect_kw("GRANT")?;
+            self.expect_kw("OPTION")?;
+        }
+        let granted_by = self.granted_by()?;
+        if self.peek().is_some() {
+            return Err(format!("unsupported GRANT clauses '{}'", tokens_text(&self.toks[self.pos..])).into())
+        }
+        objs.push(Box::new(Grant { permissions, to: roles, on, columns, all_tables, with_grant_option, granted_by }));
+        Ok(())
+    }
+    fn revoke(&mut self, objs:&mut Objects) -> Result<(), Box<dyn Error>> {
+        let grant_option_for = self.eat_kw("GRANT");
+        if grant_option_for {
+            self.expect_kw("OPTION")?;
+            self.expect_kw("FOR")?;
+        }
+        let (permissions, columns) = self.privileges()?;
+        let (on, all_tables) = self.privileges_target()?;
+        self.expect_kw("FROM")?;
+        let roles = self.roles()?;
+        let granted_by = self.granted_by()?;
+        let cascade = self.eat_kw("CASCADE");
+        if ! cascade {
+            self.eat_kw("RESTRICT");
+        }
+        if self.peek().is_some() {
+            return Err(format!("unsupported REVOKE clauses '{}'", tokens_text(&self.toks[self.pos..])).into())
+        }
+        objs.push(Box::new(Revoke { permissions, from: roles, on, columns, all_tables, grant_option_for, granted_by, cascade }));
+        Ok(())
+    }
+    fn alter(&mut self, objs:&mut Objects) -> Result<bool, Box<dyn Error>> {
+        let otype = self.object_type();
+        let of = self.object_path(otype)?;
+        if ! (self.eat_kw("OWNER") && self.eat_kw("TO")) {
+            return Ok(false)
+        }
+        let to = self.name()?;
+        if self.peek().is_some() {
+            return Ok(false)
+        }
+        objs.push(Box::new(Owner::new(&to, &of)));
+        Ok(true)
+    }
+    fn comment_on(&mut self, objs:&mut Objects) -> Result<bool, Box<dyn Error>> {
+        let column = self.eat_kw("COLUMN");
+        if ! column {
+            self.expect_kw("TABLE")?;
+        }
+        let mut parts = self.dotted()?;
+        let field = if column { parts.pop() } else { None };
+        let path = ObjectPath::new_table_from_full_name(&parts.join("."));
+        self.expect_kw("IS")?;
+        let comment = self.string()?;
+        let table = objs.iter_mut().rev()
+            .filter_map(|o| o.downcast_mut::<Table>())
+            .find(|t| t.path.is_equal(&path));
+        let table = match table {
+            Some(table) => table,
+            None => return Ok(false),
+        };
+        match field {
+            Some(field) => match table.fields.iter_mut().find(|f| f.name == field) {
+                Some(f) => f.attributes.comment = Some(comment),
+                None => return Ok(false),
+            },
+            None => table.comment = Some(comment),
+        }
+        Ok(true)
+    }
+    /// Read a statement into objs, returning false if it is not handled
+    fn statement(&mut self, objs:&mut Objects) -> Result<bool, Box<dyn Error>> {
+        if self.eat_kw("CREATE") {
+            if self.eat_kw("OR") {
+                self.expect_kw("REPLACE")?;
+            }
+            if self.eat_kw("SCHEMA") {
+                self.skip_if_not_exists();
+                let name = self.name()?;
+                let owner = if self.eat_kw("AUTHORIZATION") { self.name()? } else { "CURRENT_USER".to_owned() };
+                objs.push(Box::new(Schema::new(&name, &owner)));
+                return Ok(self.peek().is_none())
+            }
+            let unique = self.eat_kw("UNIQUE");
+            if self.eat_kw("INDEX") {
+                self.create_index(objs, unique)?;
+                return Ok(true)
+            }
+            if ! self.eat_kw("TEMP") && ! self.eat_kw("TEMPORARY") {
+                self.eat_kw("UNLOGGED");
+            }
+            if self.eat_kw("TABLE") {
+                self.create_table(objs)?;
+                return Ok(true)
+            }
+        } else if self.eat_kw("GRANT") {
+            self.grant(objs)?;
+            return Ok(true)
+        } else if self.eat_kw("REVOKE") {
+            self.revoke(objs)?;
+            return Ok(true)
+        } else if self.eat_kw("ALTER") {
+            return self.alter(objs)
+        } else if self.eat_kw("COMMENT") {
+            self.expect_kw("ON")?;
+            return self.comment_on(objs)
+        }
+        Ok(false)
+    }
+}
+
+/// First words of a statement, to report it
+fn summary(stmt:&str) -> String {
+    let words:Vec<&str> = stmt.split_whitespace().take(4).collect();
+    format!("{}...", words.join(" "))
+}
+
+/// Read SQL DDL into DBObject's, returning them with the warnings about what could not be read
+///
+/// CREATE SCHEMA, CREATE TABLE (columns with their constraints, table constraints),
+/// CREATE INDEX, GRANT, REVOKE, ALTER ... OWNER TO and COMMENT ON TABLE/COLUMN are read.
+/// Other statements and unsupported clauses are reported in the warnings
+pub fn read_sql(data:&str) -> Result<(Objects, Vec<String>), Box<dyn Error>> {
+    let mut objs = Objects::new();
+    let mut warnings = Vec::new();
+    for stmt in split_statements(data) {
+        let mut parser = Parser { toks: tokenize(&stmt), pos: 0, warnings: &mut warnings };
+        match parser.statement(&mut objs) {
+            Ok(true) => (),
+            Ok(false) => warnings.push(format!("statement not handled: {}", summary(&stmt))),
+            Err(err) => warnings.push(format!("statement not read ({err}): {}", summary(&stmt))),
+        }
+    }
+    Ok((objs, warnings))
+}
@@ -0,0 +1,257 @@
+use crate::sql::*;
+use crate::{Loader, Processor};
+use std::error::Error;
+use std::fs::File;
+use std::io::prelude::*;
+
+/// Output formats of a Diagram
+#[derive(Debug, PartialEq, Clone)]
+pub enum DiagramFormat {
+    /// Mermaid erDiagram (does not support grouping by schema)
+    Mermaid,
+    /// Graphviz DOT
+    Dot,
+    PlantUml,
+}
+
+/// Options of a Diagram
+#[derive(Debug, Clone, Default)]
+pub struct DiagramOptions {
+    /// Draw the tables of every schema inside a box
+    pub group_by_schema: bool,
+    /// Tables to draw (all of them if empty); "schema.table", "table" or "schema.*"
+    pub include: Vec<String>,
+    /// Tables not to draw; "schema.table", "table" or "schema.*"
+    pub exclude: Vec<String>,
+}
+
+impl DiagramOptions {
+    fn matches(pattern:&str, path:&ObjectPath) -> bool {
+        if let Some(schema) = pattern.strip_suffix(".*") {
+            path.schema.as_deref() == Some(schema)
+        } else {
+            pattern == path.full_name() || pattern == path.name
+        }
+    }
+    /// Should the table at path be drawn?
+    pub fn includes(&self, path:&ObjectPath) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| DiagramOptions::matches(p, path)))
+            && ! self.exclude.iter().any(|p| DiagramOptions::matches(p, path))
+    }
+}
+
+/// Column of an entity
+struct Column {
+    name: String,
+    dtype: String,
+    mandatory: bool,
+    /// PK, FK and UK markers
+    keys: Vec<&'static str>,
+}
+
+/// Relationship from the ForeignKey of a table to the referenced one
+struct Relation {
+    from: ObjectPath,
+    to: ObjectPath,
+    label: String,
+    /// All of the fields of the ForeignKey are NOT NULL
+    mandatory: bool,
+}
+
+/// Identifier for the table at path, usable in every format
+fn ident(path:&ObjectPath) -> String {
+    path.full_name().replace('.', "_")
+}
+
+/// ER diagram of the Tables and ForeignKeys in a list of DBObject's
+pub struct Diagram<'a> {
+    tables: Vec<&'a Table>,
+    type_writer: &'a dyn TypeWriter,
+    pub options: DiagramOptions,
+}
+
+impl<'a> Diagram<'a> {
+    /// Create a Diagram for objects, writing types with type_writer
+    pub fn new(objects:Vec<&'a dyn DBObject>, type_writer:&'a dyn TypeWriter) -> Self {
+        Diagram {
+            tables: objects.iter().filter_map(|o| o.downcast_ref::<Table>()).collect(),
+            type_writer,
+            options: DiagramOptions::default(),
+        }
+    }
+    /// Create a Diagram for the objects of a Processor, using its TypeWriter
+    pub fn from_processor(processor:&'a Processor) -> Self {
+        let objs = processor.objects().iter().map(|o| **o).collect();
+        Diagram::new(objs, processor.type_writer())
+    }
+    /// Create a Diagram for the objects of a Loader
+    pub fn from_loader(loader:&'a Loader, type_writer:&'a dyn TypeWriter) -> Self {
+        let objs = loader.objects().iter().map(|o| o.as_ref()).collect();
+        Diagram::new(objs, type_writer)
+    }
+    /// Set the DiagramOptions
+    pub fn with_options(mut self, options:DiagramOptions) -> Self {
+        self.options = options;
+        self
+    }
+    /// Render the diagram in format
+    pub fn render(&self, format:DiagramFormat) -> String {
+        match format {
+            DiagramFormat::Mermaid => self.to_mermaid(),
+            DiagramFormat::Dot => self.to_dot(),
+            DiagramFormat::PlantUml => self.to_plantuml(),
+        }
+    }
+    /// Write the diagram in format to a file
+    pub fn write_to_file(&self, file_name:&str, format:DiagramFormat) -> Result<(), Box<dyn Error>> {
+        let mut fh = File::create(file_name)?;
+        Ok(fh.write_all(self.render(format).as_bytes())?)
+    }
+    fn included(&self) -> Vec<&'a Table> {
+        self.tables.iter().filter(|t| self.options.includes(&t.path)).copied().collect()
+    }
+    /// Included tables, grouped by schema if requested
+    fn groups(&self) -> Vec<(Option<String>, Vec<&'a Table>)> {
+        let tables = self.included();
+        if ! self.options.group_by_schema {
+            return vec![(None, tables)]
+        }
+        let mut groups:Vec<(Option<String>, Vec<&'a Table>)> = Vec::new();
+        for table in tables {
+            match groups.iter_mut().find(|(s, _)| s == &table.path.schema) {
+                Some((_, group)) => group.push(table),
+                None => groups.push((table.path.schema.to_owned(), vec![table])),
+            }
+        }
+        groups
+    }
+    fn columns(&self, table:&Table) -> Vec<Column> {
+        let fks = table.foreign_keys();
+        table.fields.iter().map(|f| {
+            let mut keys = Vec::new();
+            if f.attributes.primary_key {
+                keys.push("PK")
+            }
+            if fks.iter().any(|fk| fk.fields.contains(&f.name)) {
+                keys.push("FK")
+            }
+            if f.attributes.unique {
+                keys.push("UK")
+            }
+            Column {
+                name: f.name.to_owned(),
+                dtype: f.attributes.dtype.to_sql(self.type_writer),
+                mandatory: ! f.attributes.empty,
+                keys,
+            }
+        }).collect()
+    }
+    fn relations(&self) -> Vec<Relation> {
+        let tables = self.included();
+        let mut rels = Vec::new();
+        for table in tables.iter() {
+            for fk in table.foreign_keys().iter() {
+                if ! tables.iter().any(|t| t.path.is_equal(&fk.ref_table)) {
+                    continue
+                }
+                let mandatory = table.fields.iter()
+                    .filter(|f| fk.fields.contains(&f.name))
+                    .all(|f| ! f.attributes.empty);
+                rels.push(Relation {
+                    from: table.path.to_owned(),
+                    to: fk.ref_table.to_owned(),
+                    label: fk.fields.join(","),
+                    mandatory,
+                })
+            }
+        }
+        rels
+    }
+    /// Render the diagram as a Mermaid erDiagram
+    pub fn to_mermaid(&self) -> String {
+        let mut s = "erDiagram\n".to_owned();
+        for table in self.included() {
+            s += format!("    {} {{\n", ident(&table.path)).as_str();
+            for c in self.columns(table) {
+                let keys = if c.keys.is_empty() { "".to_owned() } else { format!(" {}", c.keys.join(", ")) };
+                s += format!("        {} {}{keys}\n", c.dtype.replace(' ', "_"), c.name).as_str();
+            }
+            s += "    }\n";
+        }
+        for rel in self.relations() {
+            s += format!("    {} {}--o{{ {} : \"{}\"\n", ident(&rel.to),
+                if rel.mandatory { "||" } else { "|o" }, ident(&rel.from), rel.label).as_str();
+        }
+        s
+    }
+    /// Render the diagram as a Graphviz DOT digraph
+    pub fn to_dot(&self) -> String {
+        fn escape(text:&str) -> String {
+            text.chars().fold(String::new(), |mut s, c| {
+                if "{}|<>\"".contains(c) {
+                    s.push('\\')
+                }
+                s.push(c);
+                s
+            })
+        }
+        let mut s = "digraph er {\n  rankdir=LR;\n  node [shape=record];\n".to_owned();
+        for (schema, tables) in self.groups() {
+            let indent = if schema.is_some() { "    " } else { "  " };
+            if let Some(schema) = &schema {
+                s += format!("  subgraph cluster_{schema} {{\n    label=\"{}\";\n", escape(schema)).as_str();
+            }
+            for table in tables {
+                let cols:Vec<String> = self.columns(table).iter().map(|c| {
+                    let keys = if c.keys.is_empty() { "".to_owned() } else { format!(" {}", c.keys.join(",")) };
+                    format!("{} : {}{keys}\\l", escape(&c.name), escape(&c.dtype))
+                }).collect();
+                s += format!("{indent}{} [label=\"{{{}|{}}}\"];\n", ident(&table.path),
+                    escape(&table.path.full_name()), cols.concat()).as_str();
+            }
+            if schema.is_some() {
+                s += "  }\n";
+            }
+        }
+        for rel in self.relations() {
+            s += format!("  {} -> {} [label=\"{}\"];\n", ident(&rel.from), ident(&rel.to), escape(&rel.label)).as_str();
+        }
+        s + "}\n"
+    }
+    /// Render the diagram as PlantUML entities
+    pub fn to_plantuml(&self) -> String {
+        let mut s = "@startuml\n".to_owned();
+        for (schema, tables) in self.groups() {
+            let indent = if schema.is_some() { "  " } else { "" };
+            if let Some(schema) = &schema {
+                s += format!("package {schema} {{\n").as_str();
+            }
+            for table in tables {
+                s += format!("{indent}entity \"{}\" as {} {{\n", table.path.full_name(), ident(&table.path)).as_str();
+                let (pks, others):(Vec<Column>, Vec<Column>) = self.columns(table).into_iter()
+                    .partition(|c| c.keys.contains(&"PK"));
+                for c in pks.iter() {
+                    s += plantuml_column(indent, c).as_str();
+                }
+                s += format!("{indent}  --\n").as_str();
+                for c in others.iter() {
+                    s += plantuml_column(indent, c).as_str();
+                }
+                s += format!("{indent}}}\n").as_str();
+            }
+            if schema.is_some() {
+                s += "}\n";
+            }
+        }
+        for rel in self.relations() {
+            s += format!("{} }}o--{} {} : {}\n", ident(&rel.from),
+                if rel.mandatory { "||" } else { "o|" }, ident(&rel.to), rel.label).as_str();
+        }
+        s + "@enduml\n"
+    }
+}
+
+fn plantuml_column(indent:&str, c:&Column) -> String {
+    let keys:String = c.keys.iter().map(|k| format!(" <<{k}>>")).collect();
+    format!("{indent}  {}{} : {}{keys}\n", if c.mandatory { "* " } else { "" }, c.name, c.dtype)
+}
@@ -0,0 +1,539 @@
+use crate::sql::*;
+use crate::Loader;
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+
+/// Serialized form of an object, used to compare objects between models
+fn yaml<T:Serialize + ?Sized>(value:&T) -> String {
+    serde_yaml::to_string(value).unwrap_or_default()
+}
+
+/// Constraint of a Table that can be added or dropped on its own
+#[derive(Debug, Clone)]
+pub enum Constraint {
+    PrimaryKey(FieldNames),
+    Unique(FieldNames),
+    ForeignKey(ForeignKey),
+}
+
+impl Constraint {
+    /// Get the name of this Constraint in table
+    pub fn name(&self, table:&Table) -> String {
+        match self {
+            Constraint::PrimaryKey(fields) => format!("{}_{}_pk", table.path.name, fields.join("_")),
+            Constraint::Unique(fields) => format!("{}_{}_uk", table.path.name, fields.join("_")),
+            Constraint::ForeignKey(fk) => fk.name(),
+        }
+    }
+    fn to_sql(&self, table:&Table, type_writer:&dyn TypeWriter) -> String {
+        let name = format!("{}_{}", table.path.name, match self {
+            Constraint::PrimaryKey(fields) | Constraint::Unique(fields) => fields.join("_"),
+            Constraint::ForeignKey(_) => "".to_owned(),
+        });
+        match self {
+            Constraint::PrimaryKey(fields) => PrimaryKey { name, fields: fields.to_owned() }.to_sql(type_writer),
+            Constraint::Unique(fields) => UniqueKey { name, fields: fields.to_owned() }.to_sql(type_writer),
+            Constraint::ForeignKey(fk) if fk.not_valid && type_writer.supports_not_valid() => fk.to_sql(type_writer) + " NOT VALID",
+            Constraint::ForeignKey(fk) => fk.to_sql(type_writer),
+        }
+    }
+}
+
+/// Privileges, object and grantees of grant, like "SELECT (id) on shop.items to reports"
+fn grant_name(grant:&Grant) -> String {
+    let permissions:Vec<String> = grant.permissions.iter().map(|p| p.to_string()).collect();
+    let columns = if grant.columns.is_empty() { "".to_owned() } else { format!(" ({})", grant.columns.join(", ")) };
+    let on = if grant.all_tables { format!("all tables in {}", grant.on.name) } else { grant.on.full_name() };
+    format!("{}{columns} on {on} to {}", permissions.join(", "), grant.to.join(", "))
+}
+
+/// Single difference between two models, able to write the SQL applying and reverting it
+#[derive(Debug)]
+pub enum Change<'a> {
+    CreateTable(&'a Table),
+    DropTable(&'a Table),
+    AddField { table: &'a Table, field: &'a Field },
+    DropField { table: &'a Table, field: &'a Field },
+    /// Type, nullability, default value or comment of a Field changed
+    AlterField { table: &'a Table, old: &'a Field, new: &'a Field },
+    AddConstraint { table: &'a Table, constraint: Constraint },
+    DropConstraint { table: &'a Table, constraint: Constraint },
+    /// Comment of a Table changed
+    CommentTable { old: &'a Table, new: &'a Table },
+    CreateIndex(Index),
+    DropIndex(Index),
+    /// WITH GRANT OPTION or GRANTED BY of a Grant changed
+    AlterGrant { old: &'a Grant, new: &'a Grant },
+    /// Any other top level object (Schema, View, Function, Trigger, Grant...)
+    CreateObject(&'a dyn DBObject),
+    DropObject(&'a dyn DBObject),
+}
+
+/// Note written instead of an ALTER the TypeWriter can not do in place
+/// (a SchemaDiff rebuilds the Table instead)
+fn rebuild_note(table:&Table, what:&str) -> String {
+    format!("-- {} must be rebuilt to {what}", table.path.full_name())
+}
+
+fn alter_field_sql(table:&Table, from:&Field, to:&Field, type_writer:&dyn TypeWriter) -> String {
+    let t = type_writer.schema(&table.path);
+    let c = to.quoted_name();
+    match type_writer.alter_style() {
+        AlterStyle::Modify => return format!("ALTER TABLE {t} MODIFY COLUMN {};", to.to_sql(type_writer)),
+        AlterStyle::Rebuild => return rebuild_note(table, &format!("alter field {}", to.name)),
+        AlterStyle::Standard => (),
+    }
+    let (fa, ta) = (&from.attributes, &to.attributes);
+    let mut rv = Vec::new();
+    if fa.generated != ta.generated || fa.identity != ta.identity {
+        rv.push(format!("-- generation of {t}.{c} must be changed by hand"))
+    }
+    if from.sql_type(type_writer) != to.sql_type(type_writer) {
+        rv.push(format!("ALTER TABLE {t} ALTER COLUMN {c} TYPE {};", to.sql_type(type_writer)))
+    }
+    let default = to.default_sql(type_writer);
+    if from.default_sql(type_writer) != default {
+        rv.push(match default {
+            Some(def) => format!("ALTER TABLE {t} ALTER COLUMN {c} SET DEFAULT {def};"),
+            None => format!("ALTER TABLE {t} ALTER COLUMN {c} DROP DEFAULT;"),
+        })
+    }
+    if fa.empty != ta.empty {
+        rv.push(format!("ALTER TABLE {t} ALTER COLUMN {c} {} NOT NULL;", if ta.empty { "DROP" } else { "SET" }))
+    }
+    if fa.comment != ta.comment && type_writer.comment_style() == CommentStyle::CommentOn {
+        rv.push(format!("COMMENT ON COLUMN {t}.{c} IS {};", ta.comment.as_deref().map_or("NULL".to_owned(), quote_comment)))
+    }
+    rv.join("\n")
+}
+
+fn add_constraint_sql(table:&Table, constraint:&Constraint, type_writer:&dyn TypeWriter) -> String {
+    match type_writer.alter_style() {
+        AlterStyle::Rebuild => rebuild_note(table, &format!("add {}", constraint.name(table))),
+        _ => format!("ALTER TABLE {} ADD {};", type_writer.schema(&table.path), constraint.to_sql(table, type_writer)),
+    }
+}
+
+fn drop_constraint_sql(table:&Table, constraint:&Constraint, type_writer:&dyn TypeWriter) -> String {
+    let t = type_writer.schema(&table.path);
+    let name = constraint.name(table);
+    match (type_writer.alter_style(), constraint) {
+        (AlterStyle::Rebuild, _) => rebuild_note(table, &format!("drop {name}")),
+        (AlterStyle::Modify, Constraint::PrimaryKey(_)) => format!("ALTER TABLE {t} DROP PRIMARY KEY;"),
+        (AlterStyle::Modify, Constraint::Unique(_)) => format!("ALTER TABLE {t} DROP INDEX {name};"),
+        (AlterStyle::Modify, Constraint::ForeignKey(_)) => format!("ALTER TABLE {t} DROP FOREIGN KEY {name};"),
+        (AlterStyle::Standard, _) => format!("ALTER TABLE {t} DROP CONSTRAINT {name};"),
+    }
+}
+
+fn alter_grant_sql(old:&Grant, new:&Grant, type_writer:&dyn TypeWriter) -> String {
+    if old.granted_by != new.granted_by {
+        let sql = [old.drop_sql(type_writer), new.to_sql(type_writer)];
+        return sql.iter().filter(|s| ! s.is_empty()).cloned().collect::<Vec<String>>().join("\n")
+    }
+    if new.with_grant_option {
+        new.to_sql(type_writer)
+    } else {
+        Revoke { grant_option_for: true, ..old.revoke() }.to_sql(type_writer)
+    }
+}
+
+fn comment_table_sql(table:&Table, type_writer:&dyn TypeWriter) -> String {
+    let t = type_writer.schema(&table.path);
+    match type_writer.comment_style() {
+        CommentStyle::CommentOn => format!("COMMENT ON TABLE {t} IS {};", table.comment.as_deref().map_or("NULL".to_owned(), quote_comment)),
+        CommentStyle::Inline => format!("ALTER TABLE {t} COMMENT={};", quote_comment(table.comment.as_deref().unwrap_or(""))),
+        CommentStyle::SqlComment => "".to_owned(),
+    }
+}
+
+impl<'a> Change<'a> {
+    /// SQL applying this Change
+    pub fn up_sql(&self, type_writer:&dyn TypeWriter) -> String {
+        match self {
+            Change::CreateTable(table) => table.to_sql(type_writer),
+            Change::DropTable(table) => table.drop_sql(type_writer),
+            Change::AddField { table, field } =>
+                format!("ALTER TABLE {} ADD COLUMN {};", type_writer.schema(&table.path), field.to_sql(type_writer)),
+            Change::DropField { table, field } =>
+                format!("ALTER TABLE {} DROP COLUMN {};", type_writer.schema(&table.path), field.quoted_name()),
+            Change::AlterField { table, old, new } => alter_field_sql(table, old, new, type_writer),
+            Change::AddConstraint { table, constraint } => add_constraint_sql(table, constraint, type_writer),
+            Change::DropConstraint { table, constraint } => drop_constraint_sql(table, constraint, type_writer),
+            Change::CommentTable { new, .. } => comment_table_sql(new, type_writer),
+            Change::CreateIndex(index) => index.to_sql(type_writer),
+            Change::DropIndex(index) => index.drop_sql(type_writer),
+            Change::AlterGrant { old, new } => alter_grant_sql(old, new, type_writer),
+            Change::CreateObject(obj) => {
+                let post = obj.post_sql(type_writer);
+                if post.is_empty() { obj.to_sql(type_writer) } else { format!("{}\n{post}", obj.to_sql(type_writer)) }
+            },
+            Change::DropObject(obj) => obj.drop_sql(type_writer),
+        }
+    }
+    /// SQL reverting this Change
+    pub fn down_sql(&self, type_writer:&dyn TypeWriter) -> String {
+        self.reverse().up_sql(type_writer)
+    }
+    /// Get the Change undoing this one
+    pub fn reverse(&self) -> Change<'a> {
+        match self {
+            Change::CreateTable(table) => Change::DropTable(table),
+            Change::DropTable(table) => Change::CreateTable(table),
+            Change::AddField { table, field } => Change::DropField { table, field },
+            Change::DropField { table, field } => Change::AddField { table, field },
+            Change::AlterField { table, old, new } => Change::AlterField { table, old: new, new: old },
+            Change::AddConstraint { table, constraint } => Change::DropConstraint { table, constraint: constraint.to_owned() },
+            Change::DropConstraint { table, constraint } => Change::AddConstraint { table, constraint: constraint.to_owned() },
+            Change::CommentTable { old, new } => Change::CommentTable { old: new, new: old },
+            Change::CreateIndex(index) => Change::DropIndex(index.to_owned()),
+            Change::DropIndex(index) => Change::CreateIndex(index.to_owned()),
+            Change::AlterGrant { old, new } => Change::AlterGrant { old: new, new: old },
+            Change::CreateObject(obj) => Change::DropObject(*obj),
+            Change::DropObject(obj) => Change::CreateObject(*obj),
+        }
+    }
+}
+
+impl<'a> fmt::Display for Change<'a> {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        let describe = |obj:&dyn DBObject| match obj.path() {
+            Some(path) => format!("{} {}", obj.typetag_name(), path.full_name()),
+            None => obj.typetag_name().to_owned(),
+        };
+        match self {
+            Change::CreateTable(table) => write!(f, "create table {}", table.path.full_name()),
+            Change::DropTable(table) => write!(f, "drop table {}", table.path.full_name()),
+            Change::AddField { table, field } => write!(f, "add field {}.{}", table.path.full_name(), field.name),
+            Change::DropField { table, field } => write!(f, "drop field {}.{}", table.path.full_name(), field.name),
+            Change::AlterField { table, new, .. } => write!(f, "alter field {}.{}", table.path.full_name(), new.name),
+            Change::AddConstraint { table, constraint } => write!(f, "add constraint {}", constraint.name(table)),
+            Change::DropConstraint { table, constraint } => write!(f, "drop constraint {}", constraint.name(table)),
+            Change::CommentTable { new, .. } => write!(f, "comment table {}", new.path.full_name()),
+            Change::CreateIndex(index) => write!(f, "create index {}", index.name()),
+            Change::DropIndex(index) => write!(f, "drop index {}", index.name()),
+            Change::AlterGrant { new, .. } => write!(f, "alter grant {}", grant_name(new)),
+            Change::CreateObject(obj) => write!(f, "create {}", describe(*obj)),
+            Change::DropObject(obj) => write!(f, "drop {}", describe(*obj)),
+        }
+    }
+}
+
+/// Top level element of a model, in declaration order
+enum Item<'a> {
+    Table(&'a Table),
+    Index(Index),
+    Other(&'a dyn DBObject),
+}
+
+impl<'a> Item<'a> {
+    /// Identity of the element across models
+    fn key(&self) -> String {
+        match self {
+            Item::Table(table) => format!("Table {}", table.path.full_name()),
+            Item::Index(index) => format!("Index {}", yaml(index)),
+            Item::Other(obj) => match (obj.path(), obj.downcast_ref::<Grant>()) {
+                (Some(path), _) => format!("{} {}", obj.typetag_name(), path.full_name()),
+                (None, Some(grant)) => format!("Grant {}", grant_name(grant)),
+                (None, None) => yaml(*obj),
+            },
+        }
+    }
+    /// Definition of the element, for the ones compared as a whole
+    fn definition(&self) -> String {
+        match self {
+            Item::Other(obj) => yaml(*obj),
+            _ => self.key(),
+        }
+    }
+    fn grant(&self) -> Option<&'a Grant> {
+        match self {
+            Item::Other(obj) => obj.downcast_ref::<Grant>(),
+            _ => None,
+        }
+    }
+}
+
+fn items<'a>(objects:&[&'a dyn DBObject]) -> Vec<Item<'a>> {
+    let mut items = Vec::new();
+    for obj in objects.iter() {
+        if let Some(table) = obj.downcast_ref::<Table>() {
+            items.push(Item::Table(table));
+            for index in table.indexes().unwrap_or_default() {
+                items.push(Item::Index(index))
+            }
+        } else if let Some(index) = obj.downcast_ref::<Index>() {
+            items.push(Item::Index(index.to_owned()))
+        } else if obj.is_top_level() && obj.downcast_ref::<Mixin>().is_none() {
+            items.push(Item::Other(*obj))
+        }
+    }
+    items
+}
+
+fn primary_key(table:&Table) -> FieldNames {
+    table.fields.iter().filter(|f| f.attributes.primary_key).map(|f| f.name.to_owned()).collect()
+}
+
+fn unique_key(table:&Table) -> FieldNames {
+    table.fields.iter().filter(|f| f.attributes.unique).map(|f| f.name.to_owned()).collect()
+}
+
+/// Did the definition of the column change?
+fn field_changed(old:&Field, new:&Field) -> bool {
+    let (o, n) = (&old.attributes, &new.attributes);
+    o.dtype != n.dtype || o.empty != n.empty || o.defval != n.defval
+        || o.generated != n.generated || o.identity != n.identity || o.comment != n.comment
+}
+
+/// Table whose Change can not be done in place by TypeWriter's with AlterStyle::Rebuild
+fn needs_rebuild<'c>(change:&'c Change) -> Option<&'c ObjectPath> {
+    match change {
+        Change::AlterField { table, .. } | Change::AddConstraint { table, .. } | Change::DropConstraint { table, .. } => Some(&table.path),
+        _ => None,
+    }
+}
+
+/// Table altered by change, or owning the Index or Trigger it creates or drops
+fn altered_table<'c>(change:&'c Change) -> Option<&'c ObjectPath> {
+    match change {
+        Change::AddField { table, .. } | Change::DropField { table, .. } | Change::AlterField { table, .. }
+            | Change::AddConstraint { table, .. } | Change::DropConstraint { table, .. } => Some(&table.path),
+        Change::CommentTable { new, .. } => Some(&new.path),
+        Change::CreateIndex(index) | Change::DropIndex(index) => Some(&index.table),
+        Change::CreateObject(obj) | Change::DropObject(obj) => obj.downcast_ref::<Trigger>().map(|t| &t.table),
+        _ => None,
+    }
+}
+
+/// Table of objects at path
+fn table_at<'a>(objects:&[&'a dyn DBObject], path:&ObjectPath) -> Option<&'a Table> {
+    objects.iter().find_map(|o| o.downcast_ref::<Table>().filter(|t| t.path.is_equal(path)))
+}
+
+/// Statements opening a SQLite Table rebuild
+pub const REBUILD_BEGIN: &str = "PRAGMA foreign_keys=off;\nBEGIN;";
+
+/// Statements closing a SQLite Table rebuild
+pub const REBUILD_END: &str = "PRAGMA foreign_key_check;\nCOMMIT;\nPRAGMA foreign_keys=on;";
+
+/// SQLite rebuild of Table from into to: to is created as new_<table>, the common Fields
+/// are copied over, from is dropped and new_<table> renamed, then the Index'es and
+/// Trigger's of objects on the Table are recreated
+///
+/// It runs in its own transaction with foreign keys off, between REBUILD_BEGIN and REBUILD_END.
+/// Fields becoming NOT NULL take their default value where NULL, and new NOT NULL Fields need
+/// a default value
+pub(crate) fn rebuild_sql(from:&Table, to:&Table, objects:&[&dyn DBObject], type_writer:&dyn TypeWriter) -> Result<String, Box<dyn Error>> {
+    let t = type_writer.schema(&to.path);
+    let temp = type_writer.schema(&ObjectPath { name: format!("new_{}", to.path.name), ..to.path.to_owned() });
+    let mut fields = Vec::new();
+    let mut values = Vec::new();
+    for field in to.fields.iter().filter(|f| f.attributes.generated.is_none()) {
+        let default = field.default_sql(type_writer);
+        match from.fields.iter().find(|o| o.name == field.name) {
+            Some(old) => values.push(match default {
+                Some(default) if old.attributes.empty && ! field.attributes.empty => format!("COALESCE({}, {default})", field.quoted_name()),
+                _ => field.quoted_name(),
+            }),
+            None if ! field.attributes.empty && default.is_none() && field.attributes.dtype != FieldType::AutoInc =>
+                return Err(format!("{t} can not be rebuilt: new NOT NULL field {} has no default value", field.name).into()),
+            None => continue,
+        }
+        fields.push(field.quoted_name())
+    }
+    let mut rv = vec![
+        REBUILD_BEGIN.to_owned(),
+        to.to_sql(type_writer).replacen(&format!("CREATE TABLE {t} ("), &format!("CREATE TABLE {temp} ("), 1),
+        format!("INSERT INTO {temp} ({}) SELECT {} FROM {t};", fields.join(", "), values.join(", ")),
+        format!("DROP TABLE {t};"),
+        format!("ALTER TABLE {temp} RENAME TO {};", to.path.name),
+    ];
+    for item in items(objects).iter() {
+        match item {
+            Item::Index(index) if index.table.is_equal(&to.path) => rv.push(index.to_sql(type_writer)),
+            Item::Other(obj) => match obj.downcast_ref::<Trigger>() {
+                Some(trigger) if trigger.table.is_equal(&to.path) => rv.push(trigger.to_sql(type_writer)),
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+    rv.push(REBUILD_END.to_owned());
+    Ok(rv.join("\n"))
+}
+
+/// Differences between two models, as the list of Change's turning the old one into the new one
+///
+/// Changes are ordered so they can be applied one after the other: foreign keys and
+/// objects that go away are dropped first, existing Tables are altered, and new objects
+/// are created last. Fields can not be tracked across renames: they are dropped and added.
+///
+/// With TypeWriter's that can not alter Fields or constraints in place (AlterStyle::Rebuild),
+/// the SQL of every Change on such a Table is replaced by a rebuild of the whole Table,
+/// written in place of the last of those Change's
+#[derive(Debug)]
+pub struct SchemaDiff<'a> {
+    pub changes: Vec<Change<'a>>,
+    old: Vec<&'a dyn DBObject>,
+    new: Vec<&'a dyn DBObject>,
+}
+
+impl<'a> SchemaDiff<'a> {
+    /// Compare the old and new lists of DBObject's
+    pub fn new(old:Vec<&'a dyn DBObject>, new:Vec<&'a dyn DBObject>) -> Self {
+        let changes = SchemaDiff::changes(&old, &new);
+        SchemaDiff { changes, old, new }
+    }
+    fn changes(old:&[&'a dyn DBObject], new:&[&'a dyn DBObject]) -> Vec<Change<'a>> {
+        let old_items = items(old);
+        let new_items = items(new);
+        // (old, new) pairs of the Tables in both models, in new order
+        let kept:Vec<(&'a Table, &'a Table)> = new_items.iter().filter_map(|i| match (i, find_table(&old_items, i)) {
+            (Item::Table(t), Some(old)) => Some((old, *t)),
+            _ => None,
+        }).collect();
+        let mut changes = Vec::new();
+        for (old_table, new_table) in kept.iter() {
+            let new_fks:Vec<String> = new_table.foreign_keys().iter().map(yaml).collect();
+            for fk in old_table.foreign_keys().into_iter().filter(|fk| ! new_fks.contains(&yaml(fk))) {
+                changes.push(Change::DropConstraint { table: old_table, constraint: Constraint::ForeignKey(fk) })
+            }
+        }
+        for item in old_items.iter().rev() {
+            let gone = match find(&new_items, item) {
+                // the same Grant with other options is altered
+                Some(other) if other.grant().is_some() => false,
+                Some(other) => other.definition() != item.definition(),
+                None => true,
+            };
+            if gone {
+                changes.push(match item {
+                    Item::Table(table) => Change::DropTable(table),
+                    Item::Index(index) => Change::DropIndex(index.to_owned()),
+                    Item::Other(obj) => Change::DropObject(*obj),
+                })
+            }
+        }
+        for (old_table, new_table) in kept.iter() {
+            changes.append(&mut SchemaDiff::alter_table(old_table, new_table))
+        }
+        for item in new_items.iter() {
+            let new = match find(&old_items, item) {
+                Some(other) => match (other.grant(), item.grant()) {
+                    (Some(old), Some(new)) if other.definition() != item.definition() => {
+                        changes.push(Change::AlterGrant { old, new });
+                        false
+                    },
+                    _ => other.definition() != item.definition(),
+                },
+                None => true,
+            };
+            if new {
+                changes.push(match item {
+                    Item::Table(table) => Change::CreateTable(table),
+                    Item::Index(index) => Change::CreateIndex(index.to_owned()),
+                    Item::Other(obj) => Change::CreateObject(*obj),
+                })
+            }
+        }
+        for (old_table, new_table) in kept.iter() {
+            let old_fks:Vec<String> = old_table.foreign_keys().iter().map(yaml).collect();
+            for fk in new_table.foreign_keys().into_iter().filter(|fk| ! old_fks.contains(&yaml(fk))) {
+                changes.push(Change::AddConstraint { table: new_table, constraint: Constraint::ForeignKey(fk) })
+            }
+        }
+        changes
+    }
+    /// Compare the objects of two Loader's
+    pub fn from_loaders(old:&'a Loader, new:&'a Loader) -> Self {
+        SchemaDiff::new(
+            old.objects().iter().map(|o| o.as_ref()).collect(),
+            new.objects().iter().map(|o| o.as_ref()).collect())
+    }
+    fn alter_table(old:&'a Table, new:&'a Table) -> Vec<Change<'a>> {
+        let mut changes = Vec::new();
+        let keys = [
+            (primary_key(old), primary_key(new), Constraint::PrimaryKey as fn(FieldNames) -> Constraint),
+            (unique_key(old), unique_key(new), Constraint::Unique),
+        ];
+        for (old_key, new_key, constraint) in keys.iter() {
+            if old_key != new_key && ! old_key.is_empty() {
+                changes.push(Change::DropConstraint { table: old, constraint: constraint(old_key.to_owned()) })
+            }
+        }
+        for field in old.fields.iter().filter(|f| ! new.fields.iter().any(|n| n.name == f.name)) {
+            changes.push(Change::DropField { table: old, field })
+        }
+        for field in new.fields.iter() {
+            match old.fields.iter().find(|o| o.name == field.name) {
+                None => changes.push(Change::AddField { table: new, field }),
+                Some(old_field) if field_changed(old_field, field) =>
+                    changes.push(Change::AlterField { table: new, old: old_field, new: field }),
+                Some(_) => (),
+            }
+        }
+        for (old_key, new_key, constraint) in keys.iter() {
+            if old_key != new_key && ! new_key.is_empty() {
+                changes.push(Change::AddConstraint { table: new, constraint: constraint(new_key.to_owned()) })
+            }
+        }
+        if old.comment != new.comment {
+            changes.push(Change::CommentTable { old, new })
+        }
+        changes
+    }
+    /// Are both models the same?
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+    /// SQL statements turning the old model into the new one
+    pub fn up_sql(&self, type_writer:&dyn TypeWriter) -> Result<Vec<String>, Box<dyn Error>> {
+        SchemaDiff::sql(&self.changes, &self.old, &self.new, type_writer)
+    }
+    /// SQL statements turning the new model back into the old one
+    pub fn down_sql(&self, type_writer:&dyn TypeWriter) -> Result<Vec<String>, Box<dyn Error>> {
+        let changes:Vec<Change> = self.changes.iter().rev().map(|c| c.reverse()).collect();
+        SchemaDiff::sql(&changes, &self.new, &self.old, type_writer)
+    }
+    /// SQL statements applying changes, turning the from objects into the to ones
+    fn sql(changes:&[Change], from:&[&dyn DBObject], to:&[&dyn DBObject], type_writer:&dyn TypeWriter) -> Result<Vec<String>, Box<dyn Error>> {
+        let rebuilt:Vec<&ObjectPath> = if type_writer.alter_style() == AlterStyle::Rebuild {
+            changes.iter().filter_map(needs_rebuild).collect()
+        } else {
+            Vec::new()
+        };
+        let mut rv = Vec::new();
+        for (i, change) in changes.iter().enumerate() {
+            let path = altered_table(change).filter(|p| rebuilt.iter().any(|r| r.is_equal(p)));
+            let sql = match path {
+                None => change.up_sql(type_writer),
+                Some(path) if changes[i + 1..].iter().filter_map(altered_table).any(|p| p.is_equal(path)) => "".to_owned(),
+                Some(path) => match (table_at(from, path), table_at(to, path)) {
+                    (Some(old), Some(new)) => rebuild_sql(old, new, to, type_writer)?,
+                    _ => change.up_sql(type_writer),
+                },
+            };
+            if ! sql.is_empty() {
+                rv.push(sql)
+            }
+        }
+        Ok(rv)
+    }
+}
+
+/// Element of items with the same identity as item
+fn find<'i, 'a>(items:&'i [Item<'a>], item:&Item) -> Option<&'i Item<'a>> {
+    let key = item.key();
+    items.iter().find(|i| i.key() == key)
+}
+
+/// Table of items with the same path as item, if it is a Table
+fn find_table<'a>(items:&[Item<'a>], item:&Item) -> Option<&'a Table> {
+    let key = item.key();
+    items.iter().find_map(|i| match i {
+        Item::Table(table) if i.key() == key => Some(*table),
+        _ => None,
+    })
+}
@@ -0,0 +1,278 @@
+use crate::sql::*;
+use crate::{Loader, Processor};
+use std::error::Error;
+use std::fs::File;
+use std::io::prelude::*;
+
+/// Output formats of a DataDictionary
+#[derive(Debug, PartialEq, Clone)]
+pub enum DocFormat {
+    Markdown,
+    /// Self-contained HTML page
+    Html,
+}
+
+/// Formatting primitives used to write a DataDictionary
+trait Renderer {
+    fn escape(&self, text:&str) -> String;
+    fn link(&self, text:&str, anchor:&str) -> String;
+    fn heading(&self, level:usize, text:&str, anchor:Option<&str>) -> String;
+    fn paragraph(&self, text:&str) -> String;
+    fn table(&self, headers:&[&str], rows:&[Vec<String>]) -> String;
+    fn list(&self, title:&str, items:&[String]) -> String;
+    fn document(&self, title:&str, body:&str) -> String;
+}
+
+struct Markdown {}
+impl Renderer for Markdown {
+    fn escape(&self, text:&str) -> String {
+        text.replace('|', "\\|").replace('\n', " ")
+    }
+    fn link(&self, text:&str, anchor:&str) -> String {
+        format!("[{text}](#{anchor})")
+    }
+    fn heading(&self, level:usize, text:&str, anchor:Option<&str>) -> String {
+        let a = anchor.map_or("".to_owned(), |a| format!("<a id=\"{a}\"></a>\n"));
+        format!("{a}{} {text}\n\n", "#".repeat(level))
+    }
+    fn paragraph(&self, text:&str) -> String {
+        format!("{text}\n\n")
+    }
+    fn table(&self, headers:&[&str], rows:&[Vec<String>]) -> String {
+        let mut s = format!("| {} |\n|{}\n", headers.join(" | "), "---|".repeat(headers.len()));
+        for row in rows.iter() {
+            s += format!("| {} |\n", row.join(" | ")).as_str()
+        }
+        s + "\n"
+    }
+    fn list(&self, title:&str, items:&[String]) -> String {
+        let lines:Vec<String> = items.iter().map(|i| format!("- {i}\n")).collect();
+        format!("**{title}**\n\n{}\n", lines.concat())
+    }
+    fn document(&self, _title:&str, body:&str) -> String {
+        format!("{}\n", body.trim_end())
+    }
+}
+
+struct Html {}
+impl Renderer for Html {
+    fn escape(&self, text:&str) -> String {
+        text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+    }
+    fn link(&self, text:&str, anchor:&str) -> String {
+        format!("<a href=\"#{anchor}\">{text}</a>")
+    }
+    fn heading(&self, level:usize, text:&str, anchor:Option<&str>) -> String {
+        let a = anchor.map_or("".to_owned(), |a| format!(" id=\"{a}\""));
+        format!("<h{level}{a}>{text}</h{level}>\n")
+    }
+    fn paragraph(&self, text:&str) -> String {
+        format!("<p>{text}</p>\n")
+    }
+    fn table(&self, headers:&[&str], rows:&[Vec<String>]) -> String {
+        let mut s = format!("<table>\n<tr><th>{}</th></tr>\n", headers.join("</th><th>"));
+        for row in rows.iter() {
+            s += format!("<tr><td>{}</td></tr>\n", row.join("</td><td>")).as_str()
+        }
+        s + "</table>\n"
+    }
+    fn list(&self, title:&str, items:&[String]) -> String {
+        let lines:Vec<String> = items.iter().map(|i| format!("<li>{i}</li>\n")).collect();
+        format!("<h4>{title}</h4>\n<ul>\n{}</ul>\n", lines.concat())
+    }
+    fn document(&self, title:&str, body:&str) -> String {
+        format!("<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{title}</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+table {{ border-collapse: collapse; margin-bottom: 1em; }}
+th, td {{ border: 1px solid #ccc; padding: 4px 8px; text-align: left; }}
+th {{ background: #eee; }}
+</style>
+</head>
+<body>
+{body}</body>
+</html>
+")
+    }
+}
+
+/// Anchor used to link to the documentation of the object at path
+fn anchor(path:&ObjectPath) -> String {
+    path.full_name().replace('.', "-")
+}
+
+/// Data dictionary of the Tables (with their Schema's, Index'es, Grant's and Owner's) in a list of DBObject's
+pub struct DataDictionary<'a> {
+    objs: Vec<&'a dyn DBObject>,
+    type_writer: &'a dyn TypeWriter,
+    /// Title of the document
+    pub title: String,
+}
+
+impl<'a> DataDictionary<'a> {
+    /// Create a DataDictionary for objects, writing types with type_writer
+    pub fn new(objects:Vec<&'a dyn DBObject>, type_writer:&'a dyn TypeWriter) -> Self {
+        DataDictionary {
+            objs: objects,
+            type_writer,
+            title: "Data dictionary".to_owned(),
+        }
+    }
+    /// Create a DataDictionary for the objects of a Processor, using its TypeWriter
+    pub fn from_processor(processor:&'a Processor) -> Self {
+        let objs = processor.objects().iter().map(|o| **o).collect();
+        DataDictionary::new(objs, processor.type_writer())
+    }
+    /// Create a DataDictionary for the objects of a Loader
+    pub fn from_loader(loader:&'a Loader, type_writer:&'a dyn TypeWriter) -> Self {
+        let objs = loader.objects().iter().map(|o| o.as_ref()).collect();
+        DataDictionary::new(objs, type_writer)
+    }
+    /// Render the data dictionary in format
+    pub fn render(&self, format:DocFormat) -> String {
+        match format {
+            DocFormat::Markdown => self.render_with(&Markdown{}),
+            DocFormat::Html => self.render_with(&Html{}),
+        }
+    }
+    /// Render the data dictionary as Markdown
+    pub fn to_markdown(&self) -> String {
+        self.render(DocFormat::Markdown)
+    }
+    /// Render the data dictionary as a self-contained HTML page
+    pub fn to_html(&self) -> String {
+        self.render(DocFormat::Html)
+    }
+    /// Write the data dictionary in format to a file
+    pub fn write_to_file(&self, file_name:&str, format:DocFormat) -> Result<(), Box<dyn Error>> {
+        let mut fh = File::create(file_name)?;
+        Ok(fh.write_all(self.render(format).as_bytes())?)
+    }
+    fn all<T:DBObject + 'static>(&self) -> Vec<&'a T> {
+        self.objs.iter().filter_map(|o| o.downcast_ref::<T>()).collect()
+    }
+    fn render_with(&self, r:&dyn Renderer) -> String {
+        let tables = self.all::<Table>();
+        let schemas = self.all::<Schema>();
+        let mut names:Vec<Option<String>> = Vec::new();
+        if self.type_writer.supports_schemas() {
+            for schema in schemas.iter() {
+                if ! names.contains(&Some(schema.name.to_owned())) {
+                    names.push(Some(schema.name.to_owned()))
+                }
+            }
+            for table in tables.iter() {
+                if ! names.contains(&table.path.schema) {
+                    names.push(table.path.schema.to_owned())
+                }
+            }
+        } else {
+            names.push(None)
+        }
+        let by_schema = self.type_writer.supports_schemas();
+        let mut body = r.heading(1, &r.escape(&self.title), None);
+        for name in names.iter() {
+            match name {
+                Some(name) => {
+                    body += r.heading(2, &format!("Schema {}", r.escape(name)), None).as_str();
+                    for schema in schemas.iter().filter(|s| &s.name == name) {
+                        body += r.paragraph(&format!("Owner: {}", r.escape(&schema.owner))).as_str()
+                    }
+                },
+                None => body += r.heading(2, "Tables", None).as_str(),
+            }
+            for table in tables.iter().filter(|t| ! by_schema || &t.path.schema == name) {
+                body += self.table_doc(r, table, &tables).as_str()
+            }
+        }
+        r.document(&r.escape(&self.title), &body)
+    }
+    fn table_link(&self, r:&dyn Renderer, path:&ObjectPath, tables:&[&Table]) -> String {
+        let text = r.escape(&path.full_name());
+        if tables.iter().any(|t| t.path.is_equal(path)) {
+            r.link(&text, &anchor(path))
+        } else {
+            text
+        }
+    }
+    fn table_doc(&self, r:&dyn Renderer, table:&Table, tables:&[&Table]) -> String {
+        let path = &table.path;
+        let mut s = r.heading(3, &r.escape(&path.full_name()), Some(&anchor(path)));
+        if let Some(comment) = &table.comment {
+            s += r.paragraph(&r.escape(comment)).as_str()
+        }
+        let fks = table.foreign_keys();
+        let mut rows = Vec::new();
+        for f in table.fields.iter() {
+            let att = &f.attributes;
+            let mut keys = Vec::new();
+            if att.primary_key {
+                keys.push("PK".to_owned())
+            }
+            if att.unique {
+                keys.push("UK".to_owned())
+            }
+            for fk in fks.iter() {
+                if let Some(pos) = fk.fields.iter().position(|n| n == &f.name) {
+                    let field = fk.ref_fields.get(pos).map_or("".to_owned(), |rf| format!(".{}", r.escape(rf)));
+                    keys.push(format!("FK {}{field}", self.table_link(r, &fk.ref_table, tables)))
+                }
+            }
+            let default = match (&att.generated, &att.defval) {
+                (Some(gen), _) => format!("GENERATED ({})", gen.expression),
+                (None, Some(defval)) => defval.to_owned(),
+                (None, None) => "".to_owned(),
+            };
+            rows.push(vec![
+                r.escape(&f.name),
+                r.escape(&f.sql_type(self.type_writer)),
+                if att.empty { "YES" } else { "NO" }.to_owned(),
+                r.escape(&default),
+                keys.join(", "),
+                r.escape(att.comment.as_deref().unwrap_or("")),
+            ])
+        }
+        s += r.table(&["Column", "Type", "Nullable", "Default", "Keys", "Comment"], &rows).as_str();
+
+        let mut indexes:Vec<String> = table.indexes().unwrap_or_default().iter()
+            .map(|i| format!("{} ({})", r.escape(&i.name()), r.escape(&i.fields.join(", "))))
+            .collect();
+        for idx in self.all::<Index>().iter().filter(|i| i.table.is_equal(path)) {
+            indexes.push(format!("{} ({})", r.escape(&idx.name()), r.escape(&idx.fields.join(", "))))
+        }
+        if ! indexes.is_empty() {
+            s += r.list("Indexes", &indexes).as_str()
+        }
+        let fk_items:Vec<String> = fks.iter().map(|fk| {
+            format!("({}) → {} ({}) ON DELETE {} ON UPDATE {}",
+                r.escape(&fk.fields.join(", ")), self.table_link(r, &fk.ref_table, tables),
+                r.escape(&fk.ref_fields.join(", ")), fk.on_delete, fk.on_update)
+        }).collect();
+        if ! fk_items.is_empty() {
+            s += r.list("Foreign keys", &fk_items).as_str()
+        }
+        let grants:Vec<String> = self.all::<Grant>().iter()
+            .filter(|g| g.on.is_equal(path) || (g.all_tables && path.schema.as_ref() == Some(&g.on.name)))
+            .map(|g| {
+                let permissions:Vec<String> = g.permissions.iter().map(|p| p.to_string()).collect();
+                let columns = if g.columns.is_empty() { "".to_owned() } else { format!(" ({})", g.columns.join(", ")) };
+                format!("{}{} to {}{}", permissions.join(", "), r.escape(&columns), r.escape(&g.to.join(", ")),
+                    if g.with_grant_option { " (with grant option)" } else { "" })
+            })
+            .collect();
+        if ! grants.is_empty() {
+            s += r.list("Grants", &grants).as_str()
+        }
+        let owners:Vec<String> = self.all::<Owner>().iter().filter(|o| o.of.is_equal(path))
+            .map(|o| r.escape(&o.to))
+            .collect();
+        if ! owners.is_empty() {
+            s += r.list("Owner", &owners).as_str()
+        }
+        s
+    }
+}
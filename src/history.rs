@@ -0,0 +1,105 @@
+use crate::sql::*;
+
+/// Name of the field holding when the row changed
+pub const CHANGED_AT: &str = "changed_at";
+/// Name of the field holding who changed the row
+pub const CHANGED_BY: &str = "changed_by";
+/// Name of the field holding the operation (INSERT, UPDATE or DELETE)
+pub const OP: &str = "op";
+
+/// Audit objects for a Table
+///
+/// The table_history shadow Table (same Fields with their constraints relaxed, plus
+/// changed_at, changed_by and op), and the trigger function and Triggers filling it
+#[derive(Debug)]
+pub struct History {
+    pub table: Table,
+    /// Trigger function, for TypeWriters using trigger functions
+    pub function: Option<Function>,
+    pub triggers: Vec<Trigger>,
+}
+
+impl History {
+    /// Create the History objects of table for this TypeWriter
+    pub fn new(table:&Table, type_writer:&dyn TypeWriter) -> Self {
+        let path = ObjectPath {
+            schema: table.path.schema.to_owned(),
+            name: format!("{}_history", table.path.name),
+            otype: ObjectType::Table,
+        };
+        let mut fields:Fields = table.fields.iter().map(history_field).collect();
+        fields.push(Field::new(CHANGED_AT, &FieldAttributes::new_nn(FieldType::Txt)));
+        fields.push(Field::new(CHANGED_BY, &FieldAttributes::new(FieldType::Txt)));
+        fields.push(Field::new(OP, &FieldAttributes::new_nn(FieldType::Txt)));
+        let hist = Table::new(&path, fields, None);
+
+        let name = format!("{}_history", table.path.name);
+        let events = vec![TriggerEvent::Insert, TriggerEvent::Update, TriggerEvent::Delete];
+        let mut function = None;
+        let mut triggers = Vec::new();
+        if type_writer.trigger_uses_function() {
+            let fpath = ObjectPath {
+                schema: table.path.schema.to_owned(),
+                name: format!("{name}_fn"),
+                otype: ObjectType::Function,
+            };
+            let body = format!("BEGIN\n  IF TG_OP = 'DELETE' THEN\n    {}\n    RETURN OLD;\n  END IF;\n  {}\n  RETURN NEW;\nEND;",
+                insert_sql(type_writer, table, &hist, "OLD", "TG_OP"),
+                insert_sql(type_writer, table, &hist, "NEW", "TG_OP"));
+            let mut func = Function::new(&fpath, Some("trigger"), &body);
+            func.depends_on = vec![hist.path.to_owned()];
+            let mut trigger = Trigger::new(&name, &table.path, TriggerTiming::After, events);
+            trigger.function = Some(fpath);
+            function = Some(func);
+            triggers.push(trigger);
+        } else {
+            for event in events.into_iter() {
+                let (row, op) = match event {
+                    TriggerEvent::Delete => ("OLD", "'DELETE'"),
+                    TriggerEvent::Update => ("NEW", "'UPDATE'"),
+                    _ => ("NEW", "'INSERT'"),
+                };
+                let tname = format!("{}_{}", name, op.trim_matches('\'').to_lowercase());
+                let mut trigger = Trigger::new(&tname, &table.path, TriggerTiming::After, vec![event]);
+                trigger.body = Some(insert_sql(type_writer, table, &hist, row, op));
+                triggers.push(trigger);
+            }
+        }
+        History { table: hist, function, triggers }
+    }
+    /// Get all of the objects, in creation order, to be added to a Processor
+    pub fn objects(&self) -> Vec<&dyn DBObject> {
+        let mut objs:Vec<&dyn DBObject> = vec![&self.table];
+        if let Some(func) = &self.function {
+            objs.push(func)
+        }
+        for trigger in self.triggers.iter() {
+            objs.push(trigger)
+        }
+        objs
+    }
+}
+
+/// Copy of field without keys, defaults or any other constraint
+fn history_field(field:&Field) -> Field {
+    let dtype = match &field.attributes.dtype {
+        FieldType::AutoInc => match &field.attributes.identity {
+            Some(identity) => identity.width.field_type(),
+            None => FieldType::Int,
+        },
+        dt => dt.to_owned(),
+    };
+    let mut attrs = FieldAttributes::new(dtype);
+    attrs.meta_name = field.attributes.meta_name.to_owned();
+    Field::new(&field.name, &attrs)
+}
+
+/// INSERT into the history Table from the row (NEW or OLD) of table
+fn insert_sql(type_writer:&dyn TypeWriter, table:&Table, hist:&Table, row:&str, op:&str) -> String {
+    let cols:Vec<String> = hist.fields.iter().map(|f| f.quoted_name()).collect();
+    let mut values:Vec<String> = table.fields.iter().map(|f| format!("{row}.{}", f.quoted_name())).collect();
+    values.push("CURRENT_TIMESTAMP".to_owned());
+    values.push(type_writer.current_user().unwrap_or("NULL").to_owned());
+    values.push(op.to_owned());
+    format!("INSERT INTO {} ({}) VALUES ({});", type_writer.schema(&hist.path), cols.join(", "), values.join(", "))
+}
@@ -2,6 +2,7 @@
 
 mod tools;
 pub use tools::Processor;
+pub use tools::ProcessorOptions;
 pub use tools::Loader;
 
 /// TypeWriters
@@ -14,6 +15,58 @@ pub mod type_writers;
 /// Defines many type of database objects to generate SQL sql_statements from
 pub mod sql;
 
+/// History module
+///
+/// Generates audit/history tables and the triggers filling them
+pub mod history;
+
+/// Docs module
+///
+/// Data dictionary generation in Markdown and HTML
+pub mod docs;
+
+/// Diagrams module
+///
+/// ER diagram export to Mermaid, Graphviz DOT and PlantUML
+pub mod diagrams;
+
+/// DBML module
+///
+/// DBML (dbdiagram.io) writer and parser
+pub mod dbml;
+
+/// DDL module
+///
+/// Reads SQL DDL (CREATE TABLE and friends) into database objects
+pub mod ddl;
+
+/// Diff module
+///
+/// Compares two models into the changes (and SQL) turning one into the other
+pub mod diff;
+
+/// Safety module
+///
+/// Classifies the changes of a diff as safe, lock-heavy or data-losing
+pub mod safety;
+
+/// Migrations module
+///
+/// Generates migration files from model diffs and applies them, recording them in a version table
+pub mod migrations;
+
+/// Planner module
+///
+/// Expand/contract (zero-downtime) plans for field renames and type changes
+pub mod planner;
+
+/// SQLite module
+///
+/// Introspects a live SQLite database into database objects and verifies generated
+/// SQL on an in-memory one, and runs migrations on it (feature "sqlite")
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
 /// Util module
 ///
 /// Contains easy to use functions to read and write to YAML files
@@ -14,6 +14,14 @@ macro_rules! add_grant {
     }
 }
 
+#[macro_export]
+macro_rules! add_revoke {
+    // ($p:expr, $a:ident, $($args:expr),*) => {
+    ($p:expr, $($args:expr),*) => {
+        add_to_proc!($p, r, Revoke, new, $($args),*);
+    }
+}
+
 #[macro_export]
 macro_rules! add_owner {
     // ($p:expr, $a:ident, $($args:expr),*) => {
@@ -42,6 +50,7 @@ macro_rules! grant_perms {
 
 pub use add_to_proc;
 pub use add_grant;
+pub use add_revoke;
 pub use add_owner;
 pub use add_index;
 pub use grant_perms;
@@ -0,0 +1,293 @@
+use crate::diff::SchemaDiff;
+use crate::safety::SafetyCheck;
+use crate::sql::{DBObject, TypeWriter};
+use crate::util::write_yaml_to_file;
+use crate::Loader;
+use std::error::Error;
+use std::fs;
+use std::io::prelude::*;
+use std::path::Path;
+
+/// Table storing the applied migrations
+pub const MIGRATIONS_TABLE: &str = "_dml_tools_migrations";
+
+/// File (in the migrations directory) with the model the last migration was generated from
+pub const SNAPSHOT_FILE: &str = "snapshot.yaml";
+
+/// File (in the migrations directory) with the checksums of the generated migration files
+pub const CHECKSUMS_FILE: &str = "checksums.txt";
+
+/// SQL creating the MIGRATIONS_TABLE if it doesn't exist
+pub fn create_table_sql() -> String {
+    format!("CREATE TABLE IF NOT EXISTS {MIGRATIONS_TABLE} (
+  version integer NOT NULL PRIMARY KEY,
+  name text NOT NULL,
+  checksum text NOT NULL,
+  applied_at text NOT NULL DEFAULT CURRENT_TIMESTAMP
+);")
+}
+
+/// SQL listing the applied migrations as (version, name, checksum, applied_at)
+pub fn select_sql() -> String {
+    format!("SELECT version, name, checksum, applied_at FROM {MIGRATIONS_TABLE} ORDER BY version")
+}
+
+/// SQL recording migration as applied
+pub fn record_sql(migration:&Migration) -> String {
+    format!("INSERT INTO {MIGRATIONS_TABLE} (version, name, checksum) VALUES ({}, '{}', '{}');",
+        migration.version, migration.name.replace('\'', "''"), migration.checksum())
+}
+
+/// SQL removing the record of an applied migration
+pub fn unrecord_sql(version:u32) -> String {
+    format!("DELETE FROM {MIGRATIONS_TABLE} WHERE version = {version};")
+}
+
+/// 64 bit FNV-1a hash of data
+pub fn fnv1a(data:&[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, b| (hash ^ *b as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Migration read from files "V0001__name.up.sql" and (optionally) "V0001__name.down.sql"
+#[derive(Debug, Clone, PartialEq)]
+pub struct Migration {
+    pub version: u32,
+    pub name: String,
+    pub up: String,
+    pub down: Option<String>,
+}
+
+impl Migration {
+    pub fn new(version:u32, name:&str, up:&str, down:Option<&str>) -> Self {
+        Migration {
+            version,
+            name: name.to_owned(),
+            up: up.to_owned(),
+            down: down.map(|d| d.to_owned()),
+        }
+    }
+    /// Checksum (hex FNV-1a) of the up SQL, used to detect edited migrations
+    pub fn checksum(&self) -> String {
+        format!("{:016x}", fnv1a(self.up.as_bytes()))
+    }
+    /// Stem of the files of the migration, like "V0001__name"
+    pub fn file_stem(&self) -> String {
+        format!("V{:04}__{}", self.version, self.name)
+    }
+}
+
+/// Parse "V0001__name.up.sql" into (1, "name", true)
+fn parse_file_name(file_name:&str) -> Option<(u32, String, bool)> {
+    let (stem, up) = if let Some(stem) = file_name.strip_suffix(".up.sql") {
+        (stem, true)
+    } else {
+        (file_name.strip_suffix(".down.sql")?, false)
+    };
+    let (version, name) = stem.strip_prefix('V')?.split_once("__")?;
+    Some((version.parse().ok()?, name.to_owned(), up))
+}
+
+/// Read the migrations in dir, ordered by version (other files are ignored)
+pub fn read_migrations_dir<P:AsRef<Path>>(dir:P) -> Result<Vec<Migration>, Box<dyn Error>> {
+    let mut ups = Vec::new();
+    let mut downs = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let file_name = path.file_name().and_then(|f| f.to_str()).unwrap_or("");
+        if let Some((version, name, up)) = parse_file_name(file_name) {
+            let sql = fs::read_to_string(&path)?;
+            if up { ups.push((version, name, sql)) } else { downs.push((version, name, sql)) }
+        }
+    }
+    ups.sort_by_key(|(version, _, _)| *version);
+    let mut migrations:Vec<Migration> = Vec::new();
+    for (version, name, up) in ups {
+        if migrations.last().map_or(false, |m| m.version == version) {
+            return Err(format!("duplicated migration version {version}").into())
+        }
+        let down = downs.iter().position(|(v, n, _)| *v == version && n == &name)
+            .map(|pos| downs.remove(pos).2);
+        migrations.push(Migration { version, name, up, down })
+    }
+    if let Some((version, name, _)) = downs.first() {
+        return Err(format!("down migration V{version:04}__{name} without an up one").into())
+    }
+    Ok(migrations)
+}
+
+/// Migration recorded in the MIGRATIONS_TABLE
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppliedMigration {
+    pub version: u32,
+    pub name: String,
+    pub checksum: String,
+    pub applied_at: String,
+}
+
+/// Database access needed to run migrations
+pub trait MigrationDriver {
+    /// Create the MIGRATIONS_TABLE if it doesn't exist
+    fn init(&mut self) -> Result<(), Box<dyn Error>>;
+    /// Get the applied migrations, ordered by version
+    fn applied(&mut self) -> Result<Vec<AppliedMigration>, Box<dyn Error>>;
+    /// Run the up SQL of migration and record it as applied, in a single transaction
+    fn apply(&mut self, migration:&Migration) -> Result<(), Box<dyn Error>>;
+    /// Run the down SQL of migration and remove its record, in a single transaction
+    fn revert(&mut self, migration:&Migration) -> Result<(), Box<dyn Error>>;
+}
+
+/// State of a migration in the database
+#[derive(Debug, Clone, PartialEq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// Applied, but its file changed since then
+    Modified,
+    /// Applied, but its file no longer exists
+    Missing,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationStatus {
+    pub version: u32,
+    pub name: String,
+    pub state: MigrationState,
+}
+
+/// Runs an ordered list of Migration's against a database through a MigrationDriver
+pub struct Migrator {
+    migrations: Vec<Migration>,
+}
+
+impl Migrator {
+    /// Create a Migrator for migrations (they get sorted by version)
+    pub fn new(mut migrations:Vec<Migration>) -> Self {
+        migrations.sort_by_key(|m| m.version);
+        Migrator { migrations }
+    }
+    /// Create a Migrator for the migration files in dir
+    pub fn from_dir<P:AsRef<Path>>(dir:P) -> Result<Self, Box<dyn Error>> {
+        Ok(Migrator::new(read_migrations_dir(dir)?))
+    }
+    pub fn migrations(&self) -> &Vec<Migration> {
+        &self.migrations
+    }
+    /// Get the state of every known or applied migration, ordered by version
+    pub fn status(&self, driver:&mut dyn MigrationDriver) -> Result<Vec<MigrationStatus>, Box<dyn Error>> {
+        driver.init()?;
+        let applied = driver.applied()?;
+        let mut status:Vec<MigrationStatus> = self.migrations.iter().map(|m| {
+            let state = match applied.iter().find(|a| a.version == m.version) {
+                Some(a) if a.checksum == m.checksum() => MigrationState::Applied,
+                Some(_) => MigrationState::Modified,
+                None => MigrationState::Pending,
+            };
+            MigrationStatus { version: m.version, name: m.name.to_owned(), state }
+        }).collect();
+        for a in applied.iter().filter(|a| ! self.migrations.iter().any(|m| m.version == a.version)) {
+            status.push(MigrationStatus { version: a.version, name: a.name.to_owned(), state: MigrationState::Missing })
+        }
+        status.sort_by_key(|s| s.version);
+        Ok(status)
+    }
+    /// Apply the pending migrations up to version target (or all of them), returning the applied versions
+    pub fn up(&self, driver:&mut dyn MigrationDriver, target:Option<u32>) -> Result<Vec<u32>, Box<dyn Error>> {
+        let status = self.status(driver)?;
+        if let Some(s) = status.iter().find(|s| s.state == MigrationState::Modified) {
+            return Err(format!("applied migration V{:04}__{} was modified", s.version, s.name).into())
+        }
+        let last_applied = status.iter().filter(|s| s.state != MigrationState::Pending).map(|s| s.version).max();
+        let mut done = Vec::new();
+        for m in self.migrations.iter() {
+            if target.map_or(false, |t| m.version > t) {
+                break
+            }
+            if ! status.iter().any(|s| s.version == m.version && s.state == MigrationState::Pending) {
+                continue
+            }
+            if last_applied.map_or(false, |last| m.version < last) {
+                return Err(format!("pending migration {} is older than the last applied one", m.file_stem()).into())
+            }
+            driver.apply(m).map_err(|e| format!("migration {} failed: {e}", m.file_stem()))?;
+            done.push(m.version)
+        }
+        Ok(done)
+    }
+    /// Revert the last steps applied migrations, returning the reverted versions
+    pub fn down(&self, driver:&mut dyn MigrationDriver, steps:usize) -> Result<Vec<u32>, Box<dyn Error>> {
+        driver.init()?;
+        let applied = driver.applied()?;
+        let mut done = Vec::new();
+        for a in applied.iter().rev().take(steps) {
+            let m = self.migrations.iter().find(|m| m.version == a.version)
+                .ok_or_else(|| format!("applied migration V{:04}__{} not found", a.version, a.name))?;
+            if m.down.is_none() {
+                return Err(format!("migration {} has no down SQL", m.file_stem()).into())
+            }
+            driver.revert(m).map_err(|e| format!("reverting migration {} failed: {e}", m.file_stem()))?;
+            done.push(m.version)
+        }
+        Ok(done)
+    }
+}
+
+/// Get the generated files in dir that were edited (or removed) since, according to the CHECKSUMS_FILE
+pub fn modified_files<P:AsRef<Path>>(dir:P) -> Result<Vec<String>, Box<dyn Error>> {
+    let checksums = dir.as_ref().join(CHECKSUMS_FILE);
+    if ! checksums.exists() {
+        return Ok(Vec::new())
+    }
+    let mut modified = Vec::new();
+    for line in fs::read_to_string(checksums)?.lines() {
+        if let Some((file, checksum)) = line.split_once(' ') {
+            let current = fs::read(dir.as_ref().join(file)).map(|data| format!("{:016x}", fnv1a(&data)));
+            if current.ok().as_deref() != Some(checksum) {
+                modified.push(file.to_owned())
+            }
+        }
+    }
+    Ok(modified)
+}
+
+/// Write the migration named name to dir, turning the model in its SNAPSHOT_FILE (if any) into objects
+///
+/// The migration gets the version following the last one in dir; the snapshot is replaced
+/// by objects and the checksums of the new files are added to the CHECKSUMS_FILE.
+/// Nothing is written (and None returned) when the model did not change.
+/// Fails if any of the generated files was edited
+pub fn write_migration(dir:&str, name:&str, objects:Vec<&dyn DBObject>, type_writer:&dyn TypeWriter) -> Result<Option<Migration>, Box<dyn Error>> {
+    write_checked_migration(dir, name, objects, type_writer, &SafetyCheck::default())
+}
+
+/// Same as write_migration(), failing (without writing anything) if safety does not allow its changes
+pub fn write_checked_migration(dir:&str, name:&str, objects:Vec<&dyn DBObject>, type_writer:&dyn TypeWriter, safety:&SafetyCheck) -> Result<Option<Migration>, Box<dyn Error>> {
+    fs::create_dir_all(dir)?;
+    let modified = modified_files(dir)?;
+    if ! modified.is_empty() {
+        return Err(format!("generated migration files were modified: {}", modified.join(", ")).into())
+    }
+    let snapshot = Path::new(dir).join(SNAPSHOT_FILE);
+    let old = if snapshot.exists() {
+        Some(Loader::new_from_file(&snapshot.to_string_lossy())?)
+    } else {
+        None
+    };
+    let old_objects = old.as_ref().map_or(Vec::new(), |l| l.objects().iter().map(|o| o.as_ref()).collect());
+    let diff = SchemaDiff::new(old_objects, objects.to_owned());
+    if diff.is_empty() {
+        return Ok(None)
+    }
+    safety.check(&diff)?;
+    let version = read_migrations_dir(dir)?.last().map_or(1, |m| m.version + 1);
+    let up = diff.up_sql(type_writer)?.join("\n") + "\n";
+    let down = diff.down_sql(type_writer)?.join("\n") + "\n";
+    let migration = Migration::new(version, name, &up, Some(&down));
+    let mut checksums = fs::OpenOptions::new().create(true).append(true).open(Path::new(dir).join(CHECKSUMS_FILE))?;
+    for (suffix, sql) in [("up", &up), ("down", &down)] {
+        let file = format!("{}.{suffix}.sql", migration.file_stem());
+        fs::write(Path::new(dir).join(&file), sql)?;
+        checksums.write_all(format!("{file} {:016x}\n", fnv1a(sql.as_bytes())).as_bytes())?;
+    }
+    write_yaml_to_file(&snapshot.to_string_lossy(), &objects)?;
+    Ok(Some(migration))
+}
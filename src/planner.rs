@@ -0,0 +1,229 @@
+use crate::diff::{rebuild_sql, Change};
+use crate::migrations::Migration;
+use crate::sql::*;
+use std::error::Error;
+
+/// Set of statements of an expand/contract plan, deployed on its own
+#[derive(Debug, Clone, PartialEq)]
+pub struct Phase {
+    /// "expand", "backfill", "switch" or "contract"
+    pub name: String,
+    pub up: Vec<String>,
+    pub down: Vec<String>,
+}
+
+/// Expand/contract (zero-downtime) plan turning a Field of a Table into another one
+///
+/// Renames and type changes are done in four phases, letting the applications move
+/// from the old field to the new one in between:
+/// - expand: add the new field (nullable) and triggers copying the old field into it on writes
+/// - backfill: copy the old field into the new one for the existing rows
+/// - switch: drop the triggers and make the new field NOT NULL if required; type
+///   changes (done through a temporary name_new field) swap the names of the fields here,
+///   keeping the old one as name_old
+/// - contract: drop the old field
+///
+/// Keys, indexes and references of the fields are left to a regular diff. TypeWriter's
+/// with AlterStyle::Rebuild (SQLite) rebuild the Table to make the field NOT NULL.
+///
+/// The sync is one-way: writes to the new field alone are not copied back into the old
+/// one, so rolling back the switch phase after the applications wrote to the new field
+/// loses those values
+#[derive(Debug)]
+pub struct ExpandContract<'a> {
+    table: &'a Table,
+    old: Field,
+    /// Field as added in the expand phase
+    new: Field,
+    /// Final Field (with the name of the old one for type changes)
+    target: Field,
+}
+
+/// Copy of field named name without keys, references or NOT NULL
+fn relaxed(field:&Field, name:&str) -> Field {
+    let mut relaxed = Field::new(name, &field.attributes);
+    let att = &mut relaxed.attributes;
+    att.empty = true;
+    att.primary_key = false;
+    att.unique = false;
+    att.index = false;
+    att.references = None;
+    relaxed
+}
+
+/// Expression with the value of field from converted to the type of field to, if they differ
+fn converted(value:&str, from:&Field, to:&Field, type_writer:&dyn TypeWriter) -> String {
+    if from.attributes.dtype == to.attributes.dtype {
+        value.to_owned()
+    } else {
+        type_writer.cast(value, &type_writer.field_type(&to.attributes.dtype))
+    }
+}
+
+fn rename_sql(table:&Table, from:&str, to:&str, type_writer:&dyn TypeWriter) -> String {
+    format!("ALTER TABLE {} RENAME COLUMN {from} TO {to};", type_writer.schema(&table.path))
+}
+
+impl<'a> ExpandContract<'a> {
+    /// Plan renaming the field named old_name of table as new (which can also change its type)
+    pub fn rename(table:&'a Table, old_name:&str, new:&Field) -> Result<Self, Box<dyn Error>> {
+        let old = ExpandContract::field(table, old_name)?;
+        if table.fields.iter().any(|f| f.name == new.name) {
+            return Err(format!("{} already has a field {}", table.path.full_name(), new.name).into())
+        }
+        Ok(ExpandContract { table, old: old.to_owned(), new: relaxed(new, &new.name), target: new.to_owned() })
+    }
+    /// Plan changing the field of table named like new into new (to change its type)
+    pub fn change(table:&'a Table, new:&Field) -> Result<Self, Box<dyn Error>> {
+        let old = ExpandContract::field(table, &new.name)?;
+        let temp = format!("{}_new", new.name);
+        if table.fields.iter().any(|f| f.name == temp) {
+            return Err(format!("{} already has a field {temp}", table.path.full_name()).into())
+        }
+        Ok(ExpandContract { table, old: old.to_owned(), new: relaxed(new, &temp), target: new.to_owned() })
+    }
+    /// Plan an AlterField Change of a diff
+    pub fn from_change(change:&Change<'a>) -> Option<Self> {
+        match change {
+            Change::AlterField { table, old, new } => Some(ExpandContract {
+                table,
+                old: (*old).clone(),
+                new: relaxed(new, &format!("{}_new", new.name)),
+                target: (*new).clone(),
+            }),
+            _ => None,
+        }
+    }
+    fn field(table:&'a Table, name:&str) -> Result<&'a Field, Box<dyn Error>> {
+        table.fields.iter().find(|f| f.name == name)
+            .ok_or_else(|| format!("{} has no field {name}", table.path.full_name()).into())
+    }
+    /// Does the plan swap the fields in the switch phase?
+    fn swaps(&self) -> bool {
+        self.old.name == self.target.name
+    }
+    /// Name of the old field once the plan switched
+    fn old_name(&self) -> String {
+        if self.swaps() { format!("{}_old", self.old.name) } else { self.old.name.to_owned() }
+    }
+    /// Function and Triggers copying the old field into the new one on writes
+    fn sync_objects(&self, type_writer:&dyn TypeWriter) -> (Option<Function>, Vec<Trigger>) {
+        let table = &self.table.path;
+        let from = converted(&format!("NEW.{}", self.old.quoted_name()), &self.old, &self.new, type_writer);
+        let to = self.new.quoted_name();
+        let name = format!("{}_{}_sync", table.name, self.new.name);
+        let events = vec![TriggerEvent::Insert, TriggerEvent::UpdateOf(vec![self.old.name.to_owned()])];
+        if type_writer.trigger_uses_function() {
+            let fpath = ObjectPath { schema: table.schema.to_owned(), name: format!("{name}_fn"), otype: ObjectType::Function };
+            let func = Function::new(&fpath, Some("trigger"), &format!("BEGIN\n  NEW.{to} := {from};\n  RETURN NEW;\nEND;"));
+            let mut trigger = Trigger::new(&name, table, TriggerTiming::Before, events);
+            trigger.function = Some(fpath);
+            (Some(func), vec![trigger])
+        } else if type_writer.trigger_sets_new() {
+            let mut trigger = Trigger::new(&name, table, TriggerTiming::Before, events);
+            trigger.body = Some(format!("SET NEW.{to} = {from};"));
+            (None, vec![trigger])
+        } else {
+            let keys:Vec<String> = self.table.fields.iter().filter(|f| f.attributes.primary_key)
+                .map(|f| format!("{0} = NEW.{0}", f.quoted_name())).collect();
+            // WITHOUT ROWID tables have no rowid, but always a PRIMARY KEY
+            let row = if keys.is_empty() { "rowid = NEW.rowid".to_owned() } else { keys.join(" AND ") };
+            let mut trigger = Trigger::new(&name, table, TriggerTiming::After, events);
+            trigger.body = Some(format!("UPDATE {} SET {to} = {from} WHERE {row};", type_writer.schema(table)));
+            (None, vec![trigger])
+        }
+    }
+    fn create_sync(&self, type_writer:&dyn TypeWriter) -> Vec<String> {
+        let (func, triggers) = self.sync_objects(type_writer);
+        let mut objs:Vec<&dyn DBObject> = Vec::new();
+        if let Some(func) = &func {
+            objs.push(func)
+        }
+        for trigger in triggers.iter() {
+            objs.push(trigger)
+        }
+        objs.iter().map(|o| o.to_sql(type_writer)).filter(|s| ! s.is_empty()).collect()
+    }
+    fn drop_sync(&self, type_writer:&dyn TypeWriter) -> Vec<String> {
+        let (func, triggers) = self.sync_objects(type_writer);
+        let mut rv:Vec<String> = triggers.iter().map(|t| t.drop_sql(type_writer)).collect();
+        if let Some(func) = &func {
+            rv.push(func.drop_sql(type_writer))
+        }
+        rv.into_iter().filter(|s| ! s.is_empty()).collect()
+    }
+    fn copy_sql(&self, type_writer:&dyn TypeWriter, from:&Field, to:&Field) -> String {
+        format!("UPDATE {} SET {} = {} WHERE {} IS NULL;", type_writer.schema(&self.table.path),
+            to.quoted_name(), converted(&from.quoted_name(), from, to, type_writer), to.quoted_name())
+    }
+    /// Table once switched, with the final field still nullable and NOT NULL as required
+    fn switched_tables(&self) -> (Table, Table) {
+        let mut fields:Fields = self.table.fields.iter().map(|f| if f.name == self.old.name {
+            Field::new(&self.old_name(), &f.attributes)
+        } else {
+            f.to_owned()
+        }).collect();
+        fields.push(relaxed(&self.target, &self.target.name));
+        let nullable = Table { fields: fields.to_owned(), ..self.table.to_owned() };
+        if let Some(last) = fields.last_mut() {
+            last.attributes.empty = self.target.attributes.empty
+        }
+        (nullable, Table { fields, ..self.table.to_owned() })
+    }
+    /// Statements making the final field NOT NULL, and back
+    fn not_null_sql(&self, type_writer:&dyn TypeWriter) -> Result<(String, String), Box<dyn Error>> {
+        if type_writer.alter_style() == AlterStyle::Rebuild {
+            let (nullable, not_null) = self.switched_tables();
+            return Ok((rebuild_sql(&nullable, &not_null, &[&not_null], type_writer)?,
+                rebuild_sql(&not_null, &nullable, &[&nullable], type_writer)?))
+        }
+        let final_field = relaxed(&self.target, &self.target.name);
+        let change = Change::AlterField { table: self.table, old: &final_field, new: &self.target };
+        Ok((change.up_sql(type_writer), change.down_sql(type_writer)))
+    }
+    /// Get the phases of the plan, with their statements written for type_writer
+    pub fn phases(&self, type_writer:&dyn TypeWriter) -> Result<Vec<Phase>, Box<dyn Error>> {
+        let table = self.table;
+        let (old, new) = (self.old.quoted_name(), self.new.quoted_name());
+        let mut expand = vec![Change::AddField { table, field: &self.new }.up_sql(type_writer)];
+        expand.append(&mut self.create_sync(type_writer));
+        let mut unexpand = self.drop_sync(type_writer);
+        unexpand.push(Change::DropField { table, field: &self.new }.up_sql(type_writer));
+
+        // NOT NULL is set once the field has its final name
+        let mut switch = self.drop_sync(type_writer);
+        let mut unswitch = Vec::new();
+        if self.swaps() {
+            switch.push(rename_sql(table, &old, &self.old_name(), type_writer));
+            switch.push(rename_sql(table, &new, &self.target.quoted_name(), type_writer));
+            unswitch.push(rename_sql(table, &self.target.quoted_name(), &new, type_writer));
+            unswitch.push(rename_sql(table, &self.old_name(), &old, type_writer));
+        }
+        if ! self.target.attributes.empty {
+            let (not_null, nullable) = self.not_null_sql(type_writer)?;
+            switch.push(not_null);
+            unswitch.insert(0, nullable);
+        }
+        unswitch.append(&mut self.create_sync(type_writer));
+
+        let old_field = relaxed(&self.old, &self.old_name());
+        let contract = vec![Change::DropField { table, field: &old_field }.up_sql(type_writer)];
+        let uncontract = vec![
+            Change::AddField { table, field: &old_field }.up_sql(type_writer),
+            self.copy_sql(type_writer, &self.target, &old_field),
+        ];
+        Ok(vec![
+            Phase { name: "expand".to_owned(), up: expand, down: unexpand },
+            Phase { name: "backfill".to_owned(), up: vec![self.copy_sql(type_writer, &self.old, &self.new)], down: Vec::new() },
+            Phase { name: "switch".to_owned(), up: switch, down: unswitch },
+            Phase { name: "contract".to_owned(), up: contract, down: uncontract },
+        ])
+    }
+    /// Get the phases of the plan as Migration's named name_phase, starting at version
+    pub fn migrations(&self, version:u32, name:&str, type_writer:&dyn TypeWriter) -> Result<Vec<Migration>, Box<dyn Error>> {
+        Ok(self.phases(type_writer)?.into_iter().enumerate().map(|(i, phase)| {
+            let down = phase.down.join("\n") + "\n";
+            Migration::new(version + i as u32, &format!("{name}_{}", phase.name), &(phase.up.join("\n") + "\n"), Some(&down))
+        }).collect())
+    }
+}
@@ -0,0 +1,126 @@
+use crate::diff::{Change, Constraint, SchemaDiff};
+use crate::sql::*;
+use std::error::Error;
+use std::fmt;
+
+/// How dangerous applying a Change is
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Risk {
+    Safe,
+    /// Locks or rewrites a whole Table, or can fail on the rows already there
+    LockHeavy,
+    /// Throws away data (or a guarantee on it) that reverting does not bring back
+    DataLosing,
+}
+impl fmt::Display for Risk {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Risk::Safe=>"safe",
+            Risk::LockHeavy=>"lock-heavy",
+            Risk::DataLosing=>"data-losing",
+        })
+    }
+}
+
+/// Classification of a Change
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// Description of the Change, as written by its Display
+    pub change: String,
+    pub risk: Risk,
+    pub reason: Option<String>,
+}
+
+/// Is changing a column from type from to type to losing values (or precision)?
+pub fn narrows(from:&FieldType, to:&FieldType) -> bool {
+    use FieldType::*;
+    let widens = match from {
+        Bool => vec![Bool, Int, BigInt, Dbl, Txt],
+        Int | AutoInc => vec![Int, AutoInc, BigInt, Dbl, Txt],
+        BigInt => vec![BigInt, Txt],
+        Dbl => vec![Dbl, Txt],
+        Txt => vec![Txt],
+    };
+    ! widens.contains(to)
+}
+
+fn classify(change:&Change, created:&[&ObjectPath]) -> (Risk, Option<String>) {
+    let is_new = |path:&ObjectPath| created.iter().any(|p| p.is_equal(path));
+    match change {
+        Change::DropTable(_) => (Risk::DataLosing, Some("drops the table with all of its rows".to_owned())),
+        Change::DropField { .. } => (Risk::DataLosing, Some("drops the column with all of its values".to_owned())),
+        Change::AlterField { old, new, .. } => {
+            let (o, n) = (&old.attributes, &new.attributes);
+            if narrows(&o.dtype, &n.dtype) {
+                (Risk::DataLosing, Some(format!("narrows the type from {:?} to {:?}", o.dtype, n.dtype)))
+            } else if o.empty && ! n.empty && n.defval.is_none() {
+                (Risk::LockHeavy, Some("sets NOT NULL without a default, failing on existing NULLs".to_owned()))
+            } else if o.dtype != n.dtype {
+                (Risk::LockHeavy, Some("changes the type, rewriting the table".to_owned()))
+            } else {
+                (Risk::Safe, None)
+            }
+        },
+        Change::AddField { table, field } if ! is_new(&table.path) && ! field.attributes.empty && field.attributes.defval.is_none() =>
+            (Risk::LockHeavy, Some("adds a NOT NULL column without a default, failing on existing rows".to_owned())),
+        Change::DropConstraint { constraint: Constraint::PrimaryKey(_) | Constraint::Unique(_), .. } =>
+            (Risk::DataLosing, Some("drops a uniqueness guarantee, restoring it fails once duplicates exist".to_owned())),
+        Change::AddConstraint { constraint: Constraint::ForeignKey(fk), .. } if ! fk.not_valid =>
+            (Risk::LockHeavy, Some("checks every existing row while locking the table, consider not_valid".to_owned())),
+        Change::AddConstraint { constraint: Constraint::PrimaryKey(_) | Constraint::Unique(_), table } if ! is_new(&table.path) =>
+            (Risk::LockHeavy, Some("builds a unique index while locking the table".to_owned())),
+        Change::CreateIndex(index) if ! is_new(&index.table) =>
+            (Risk::LockHeavy, Some("builds an index while blocking writes to the table".to_owned())),
+        Change::DropObject(obj) if obj.downcast_ref::<Sequence>().is_some() =>
+            (Risk::DataLosing, Some("drops the sequence with its current value".to_owned())),
+        _ => (Risk::Safe, None),
+    }
+}
+
+/// Classify every Change of diff, in the same order
+pub fn analyze(diff:&SchemaDiff) -> Vec<Finding> {
+    let created:Vec<&ObjectPath> = diff.changes.iter().filter_map(|c| match c {
+        Change::CreateTable(table) => Some(&table.path),
+        _ => None,
+    }).collect();
+    diff.changes.iter().map(|change| {
+        let (risk, reason) = classify(change, &created);
+        Finding { change: change.to_string(), risk, reason }
+    }).collect()
+}
+
+/// Policy about which Change's can be written
+///
+/// In strict mode every data-losing Change must be acknowledged by its description
+/// (like "drop field shop.customers.phone") to be written
+#[derive(Debug, Clone, Default)]
+pub struct SafetyCheck {
+    pub strict: bool,
+    pub acknowledged: Vec<String>,
+}
+
+impl SafetyCheck {
+    /// Create a strict SafetyCheck, with no acknowledged Change's
+    pub fn new_strict() -> Self {
+        SafetyCheck { strict: true, acknowledged: Vec::new() }
+    }
+    /// Acknowledge the Change described by change
+    pub fn acknowledge(mut self, change:&str) -> Self {
+        self.acknowledged.push(change.to_owned());
+        self
+    }
+    /// Classify the Change's of diff, failing if a data-losing one is not allowed
+    pub fn check(&self, diff:&SchemaDiff) -> Result<Vec<Finding>, Box<dyn Error>> {
+        let findings = analyze(diff);
+        if self.strict {
+            let refused:Vec<&str> = findings.iter()
+                .filter(|f| f.risk == Risk::DataLosing && ! self.acknowledged.contains(&f.change))
+                .map(|f| f.change.as_str())
+                .collect();
+            if ! refused.is_empty() {
+                return Err(format!("data-losing changes not acknowledged: {}", refused.join(", ")).into())
+            }
+        }
+        Ok(findings)
+    }
+}
@@ -1,6 +1,8 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::any::Any;
 use std::collections::HashSet;
-use std::fmt::Debug;
+use std::error::Error;
+use std::fmt::{self, Debug};
 use std::option::*;
 use linked_hash_map::LinkedHashMap;
 
@@ -22,20 +24,139 @@ pub trait TypeWriter {
     fn supports_permissions(&self) -> bool { true }
     fn supports_auto_increment(&self) -> bool { true }
     fn supports_sequences(&self) -> bool { false }
+    fn supports_fk_action(&self, _action:&FKOn) -> bool { true }
+    fn supports_fk_match(&self) -> bool { true }
+    fn supports_deferrable(&self) -> bool { true }
+    /// Can foreign keys be added NOT VALID (checking only the new rows)?
+    fn supports_not_valid(&self) -> bool { true }
+    fn supports_identity(&self) -> bool { false }
+    /// Identity used for AutoInc fields not declaring their own (when identity is supported)
+    fn identity(&self) -> Option<Identity> { None }
+    fn identity_type(&self, identity:&Identity) -> String {
+        format!("{} GENERATED {} AS IDENTITY", self.field_type(&identity.width.field_type()), identity.generated)
+    }
+    /// Can generated columns be VIRTUAL? (STORED is used otherwise)
+    fn supports_virtual_columns(&self) -> bool { false }
+    fn supports_materialized_views(&self) -> bool { true }
+    fn supports_view_check_option(&self) -> bool { true }
+    fn supports_functions(&self) -> bool { true }
+    /// Delimiter used around routine bodies (MySQL's DELIMITER), None for dollar quoted bodies
+    fn routine_delimiter(&self) -> Option<&str> { None }
+    /// Do triggers call a function? (otherwise they have an inline body)
+    fn trigger_uses_function(&self) -> bool { true }
+    fn supports_multi_event_triggers(&self) -> bool { true }
+    /// Supports FOR EACH STATEMENT (and TRUNCATE) triggers?
+    fn supports_statement_triggers(&self) -> bool { true }
+    fn supports_trigger_when(&self) -> bool { true }
+    fn supports_trigger_update_of(&self) -> bool { true }
+    fn supports_instead_of_triggers(&self) -> bool { true }
+    /// Can BEFORE triggers assign NEW values? (otherwise the row is updated by an AFTER trigger)
+    fn trigger_sets_new(&self) -> bool { true }
+    /// SQL expression for the current user, if any
+    fn current_user(&self) -> Option<&str> { Some("CURRENT_USER") }
+    /// How Table and Field comments are written
+    fn comment_style(&self) -> CommentStyle { CommentStyle::CommentOn }
+    /// Is the AutoInc field rendered as the (inline) PRIMARY KEY?
+    fn auto_increment_is_primary_key(&self) -> bool { ! self.supports_auto_increment() }
+    /// How existing Tables are altered
+    fn alter_style(&self) -> AlterStyle { AlterStyle::Standard }
+    /// Can DDL statements be rolled back as part of a transaction?
+    fn supports_transactional_ddl(&self) -> bool { true }
+    /// Supports CREATE TABLE and CREATE SCHEMA IF NOT EXISTS?
+    fn supports_if_not_exists(&self) -> bool { true }
+    fn supports_index_if_not_exists(&self) -> bool { self.supports_if_not_exists() }
+    /// Supports GRANTED BY in GRANT and REVOKE?
+    fn supports_granted_by(&self) -> bool { true }
+    /// Target of GRANT and REVOKE on all of the tables of schema
+    fn all_tables_in_schema(&self, schema:&str) -> String { format!("ALL TABLES IN SCHEMA {schema}") }
+    /// Expression converting value to sql_type
+    fn cast(&self, value:&str, sql_type:&str) -> String { format!("CAST({value} AS {sql_type})") }
+}
+
+/// Ways of writing Table and Field comments
+#[derive(Debug, PartialEq, Clone)]
+pub enum CommentStyle {
+    /// COMMENT ON TABLE/COLUMN statements after CREATE TABLE
+    CommentOn,
+    /// COMMENT '...' clauses in the CREATE TABLE
+    Inline,
+    /// -- comments in the CREATE TABLE, kept by the database as part of its definition
+    SqlComment,
+}
+
+/// Ways of altering existing Tables
+#[derive(Debug, PartialEq, Clone)]
+pub enum AlterStyle {
+    /// ALTER COLUMN ... TYPE/SET NOT NULL/SET DEFAULT and DROP CONSTRAINT
+    Standard,
+    /// MODIFY COLUMN with the whole definition, DROP FOREIGN KEY/INDEX/PRIMARY KEY
+    Modify,
+    /// Only ADD/DROP COLUMN, any other change needs the Table to be rebuilt
+    Rebuild,
+}
+
+/// Quote comment as an SQL string literal
+pub(crate) fn quote_comment(comment:&str) -> String {
+    format!("'{}'", comment.replace('\'', "''"))
+}
+
+/// Write comment as -- SQL comment lines, each one followed by separator
+fn sql_comment(comment:&str, separator:&str) -> String {
+    comment.lines().map(|l| format!("-- {l}{separator}")).collect()
+}
+
+/// Access to the concrete type behind a trait object
+pub trait AsAny {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+impl<T:Any> AsAny for T {
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
 }
 
 /// Trait for serializing a database object to as String
 // #[typetag::serde(tag = "type")]
 #[typetag::serde(tag = "tag")]
-pub trait DBObject : Debug {
+pub trait DBObject : Debug + AsAny {
     fn to_sql(&self, type_writer:&dyn TypeWriter) -> String;
     fn is_top_level(&self) -> bool { false }
+    /// Path of the object created by this DBObject, if any
+    fn path(&self) -> Option<&ObjectPath> { None }
+    /// Objects that must be created before this one
+    fn depends_on(&self) -> Vec<ObjectPath> { Vec::new() }
+    /// SQL dropping what to_sql() creates (empty if there is nothing to drop)
+    fn drop_sql(&self, _type_writer:&dyn TypeWriter) -> String { "".to_owned() }
+    /// SQL to run once every object has been created (empty if there is nothing to run)
+    fn post_sql(&self, _type_writer:&dyn TypeWriter) -> String { "".to_owned() }
+    #[allow(clippy::borrowed_box)]
     fn top_level_to_sql(&self, _type_writer:&dyn TypeWriter, _delayed: &Vec<&Box<& dyn DBObject>>) -> String {
         if self.is_top_level() {
             panic!("should not run a non-reimplemented top_level_to_sql()!")
         }
         "".to_owned()
     }
+    /// SQL of top_level_to_sql() (to_sql() without delayed objects) skipping the creation
+    /// if the object already exists, when the object and type_writer support IF NOT EXISTS
+    #[allow(clippy::borrowed_box)]
+    fn if_not_exists_sql(&self, type_writer:&dyn TypeWriter, delayed: &Vec<&Box<& dyn DBObject>>) -> String {
+        if delayed.is_empty() {
+            self.to_sql(type_writer)
+        } else {
+            self.top_level_to_sql(type_writer, delayed)
+        }
+    }
+}
+
+impl<'a> dyn DBObject + 'a {
+    /// Get the concrete object, if it is a T
+    pub fn downcast_ref<T:DBObject + 'static>(&self) -> Option<&T> {
+        self.as_any().downcast_ref::<T>()
+    }
+    /// Get the concrete object mutably, if it is a T
+    pub fn downcast_mut<T:DBObject + 'static>(&mut self) -> Option<&mut T> {
+        self.as_any_mut().downcast_mut::<T>()
+    }
 }
 
 fn default_false() -> bool {
@@ -55,10 +176,10 @@ fn is_default_dtype(t:&FieldType) -> bool {
 }
 
 fn is_default_false(b:&bool) -> bool {
-    *b == false
+    !*b
 }
 fn is_default_true(b:&bool) -> bool {
-    *b == true
+    *b
 }
 fn is_none(opt:&Option<String>) -> bool {
     opt.is_none()
@@ -79,10 +200,26 @@ pub enum FieldType {
     #[serde(rename = "auto_increment")]
     AutoInc,
 }
+impl FieldType {
+    /// Get the FieldType for an SQL (or DBML) type name, ignoring its length/precision
+    pub fn from_sql_type(name:&str) -> Option<FieldType> {
+        let base = name.split('(').next().unwrap_or("").trim().to_lowercase();
+        Some(match base.as_str() {
+            "int"|"integer"|"int4"|"int2"|"smallint"|"mediumint"|"tinyint" => FieldType::Int,
+            "bigint"|"int8" => FieldType::BigInt,
+            "text"|"varchar"|"char"|"character"|"character varying"|"nvarchar"|"nchar"
+                |"string"|"tinytext"|"mediumtext"|"longtext"|"clob"|"uuid" => FieldType::Txt,
+            "double"|"double precision"|"float"|"float4"|"float8"|"real"|"numeric"|"decimal" => FieldType::Dbl,
+            "bool"|"boolean"|"bit" => FieldType::Bool,
+            "serial"|"serial4"|"bigserial"|"serial8"|"integer auto_increment" => FieldType::AutoInc,
+            _ => return None,
+        })
+    }
+}
 #[typetag::serde]
 impl DBObject for FieldType {
     fn to_sql(&self, type_writer:&dyn TypeWriter) -> String {
-        type_writer.field_type(&self)
+        type_writer.field_type(self)
     }
 }
 
@@ -125,6 +262,18 @@ pub struct FieldAttributes {
     // name when searching in InterData
     #[serde(skip_serializing_if = "is_none")]
     pub meta_name: Option<String>,
+    /// Optional IDENTITY definition for AutoInc fields
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity: Option<Identity>,
+    /// Optional expression for generated (computed) fields
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generated: Option<Generated>,
+    /// Optional single field ForeignKey (expanded by Table)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub references: Option<FieldReference>,
+    /// Optional documentation for this field
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "description")]
+    pub comment: Option<String>,
 }
 impl FieldAttributes {
     fn new_default(dt:FieldType) -> Self {
@@ -138,6 +287,10 @@ impl FieldAttributes {
             index: false,
             only_db: false,
             meta_name: None,
+            identity: None,
+            generated: None,
+            references: None,
+            comment: None,
         }
     }
     /// Initialize default FieldAttributes for this FieldType
@@ -162,6 +315,10 @@ impl FieldAttributes {
         me.defval = Some(defval.to_string());
         me
     }
+    /// Initialize NOT NULL FieldAttributes for this FieldType taking its default value from a Sequence
+    pub fn new_nn_seq(dt:FieldType, seq:&ObjectPath) -> Self {
+        FieldAttributes::new_nn_def(dt, nextval(seq).as_str())
+    }
     /// Initialize PrimaryKey FieldAttributes for this FieldType
     pub fn new_pk(dt:FieldType) -> Self {
         let mut me = FieldAttributes::new_nn(dt);
@@ -185,6 +342,135 @@ impl FieldAttributes {
         me.meta_name = Some(meta.to_string());
         me
     }
+    /// Initialize PrimaryKey AutoInc FieldAttributes using an IDENTITY column (where supported)
+    pub fn new_identity_pk(identity:&Identity) -> Self {
+        let mut me = FieldAttributes::new_pk(FieldType::AutoInc);
+        me.identity = Some(identity.to_owned());
+        me
+    }
+    /// Initialize FieldAttributes for this FieldType generated from expression
+    pub fn new_generated(dt:FieldType, expression:&str, storage:GeneratedStorage) -> Self {
+        let mut me = FieldAttributes::new_default(dt);
+        me.generated = Some(Generated{ expression: expression.to_string(), storage });
+        me
+    }
+    /// Is this field computed by the database? (should not be written by code generated from the model)
+    pub fn read_only(&self) -> bool {
+        self.generated.is_some()
+            || matches!(&self.identity, Some(identity) if identity.generated == IdentityGeneration::Always)
+    }
+    /// Initialize FieldAttributes for this FieldType referencing field of table ("schema.table")
+    pub fn new_ref(dt:FieldType, table:&str, field:&str) -> Self {
+        let mut me = FieldAttributes::new_default(dt);
+        me.references = Some(FieldReference::new(table, field));
+        me
+    }
+}
+
+/// Types of IDENTITY column generation
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub enum IdentityGeneration {
+    #[default]
+    ByDefault,
+    Always,
+}
+impl fmt::Display for IdentityGeneration {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            IdentityGeneration::ByDefault=>"BY DEFAULT",
+            IdentityGeneration::Always=>"ALWAYS",
+        })
+    }
+}
+
+/// Width of IDENTITY columns
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub enum IdentityWidth {
+    #[default]
+    #[serde(rename = "int")]
+    Int,
+    #[serde(rename = "bigint")]
+    BigInt,
+}
+impl IdentityWidth {
+    /// Get the FieldType for this width
+    pub fn field_type(&self) -> FieldType {
+        match self {
+            IdentityWidth::Int=>FieldType::Int,
+            IdentityWidth::BigInt=>FieldType::BigInt,
+        }
+    }
+}
+
+/// IDENTITY column, an alternative to serial for AutoInc fields
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct Identity {
+    #[serde(default)]
+    pub generated: IdentityGeneration,
+    #[serde(default)]
+    pub width: IdentityWidth,
+}
+impl Identity {
+    /// Create an Identity
+    pub fn new(generated:IdentityGeneration, width:IdentityWidth) -> Self {
+        Identity { generated, width }
+    }
+}
+
+/// Storage of generated columns
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub enum GeneratedStorage {
+    #[default]
+    Stored,
+    Virtual,
+}
+impl fmt::Display for GeneratedStorage {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            GeneratedStorage::Stored=>"STORED",
+            GeneratedStorage::Virtual=>"VIRTUAL",
+        })
+    }
+}
+
+/// Generated (computed) column definition
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Generated {
+    pub expression: String,
+    #[serde(default)]
+    pub storage: GeneratedStorage,
+}
+
+/// Single field ForeignKey shorthand for FieldAttributes
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct FieldReference {
+    /// Referenced table, as "schema.table" or "table"
+    pub table: String,
+    /// Referenced field
+    pub field: String,
+    #[serde(default="default_on_clause")]
+    pub on_delete: FKOn,
+    #[serde(default="default_on_clause")]
+    pub on_update: FKOn,
+}
+impl FieldReference {
+    /// Create a FieldReference to field of table ("schema.table") with the default ON clauses
+    pub fn new(table:&str, field:&str) -> Self {
+        FieldReference {
+            table: table.to_string(),
+            field: field.to_string(),
+            on_delete: default_on_clause(),
+            on_update: default_on_clause(),
+        }
+    }
+    /// Expand this reference from field of table into a ForeignKey
+    pub fn to_foreign_key(&self, table:&ObjectPath, field:&str) -> ForeignKey {
+        let mut fk = ForeignKey::new(table, &vec![field.to_owned()],
+            &ObjectPath::new_table_from_full_name(&self.table), &vec![self.field.to_owned()]);
+        fk.on_delete = self.on_delete.to_owned();
+        fk.on_update = self.on_update.to_owned();
+        fk
+    }
 }
 
 /// Field of a Table
@@ -201,6 +487,40 @@ impl Field {
             attributes:attrs.clone(),
         }
     }
+    /// Get the name of this Field as used in SQL statements
+    pub fn quoted_name(&self) -> String {
+        // TODO: escape all SQL reserved words
+        match self.name.as_str() {
+            "role"=>"\"role\"".to_owned(),
+            _=>self.name.to_owned(),
+        }
+    }
+    /// Get the SQL type of this Field (including IDENTITY) for type_writer
+    pub fn sql_type(&self, type_writer:&dyn TypeWriter) -> String {
+        let att = &self.attributes;
+        let identity = match &att.identity {
+            Some(identity) => Some(identity.to_owned()),
+            None => type_writer.identity(),
+        };
+        match identity {
+            Some(identity) if att.dtype == FieldType::AutoInc && type_writer.supports_identity() => type_writer.identity_type(&identity),
+            _ => att.dtype.to_sql(type_writer),
+        }
+    }
+    /// Get the DEFAULT value of this Field as written for type_writer, if any
+    pub fn default_sql(&self, type_writer:&dyn TypeWriter) -> Option<String> {
+        let att = &self.attributes;
+        att.defval.as_ref()
+            .filter(|d| att.generated.is_none() && (type_writer.supports_sequences() || ! is_nextval(d)))
+            .map(|def| match att.dtype {
+                FieldType::Txt => format!("\"{}\"", def),
+                _=>def.to_owned()
+            })
+    }
+    /// Is this Field computed by the database?
+    pub fn read_only(&self) -> bool {
+        self.attributes.read_only()
+    }
     /// Initialize Field named name with FieldAttributes with only_db attributes on
     pub fn new_only_db(name:&str, attrs:&FieldAttributes) -> Self {
         let mut me = Field::new(name, attrs);
@@ -211,23 +531,31 @@ impl Field {
 #[typetag::serde]
 impl DBObject for Field {
     fn to_sql(&self, type_writer:&dyn TypeWriter) -> String {
-        // TODO: escape all SQL reserved words
-        let mut s = match self.name.as_str() {
-            "role"=>format!("\"role\""),
-            _=>self.name.to_owned(),
-        };            
+        let mut s = self.quoted_name();            
         let att = &self.attributes;
-        s += format!(" {} ", att.dtype.to_sql(type_writer)).as_str();
+        s += format!(" {} ", self.sql_type(type_writer)).as_str();
+        if let Some(gen) = &att.generated {
+            let storage = if type_writer.supports_virtual_columns() {
+                gen.storage.to_owned()
+            } else {
+                GeneratedStorage::Stored
+            };
+            s += format!("GENERATED ALWAYS AS ({}) {} ", gen.expression, storage).as_str();
+        }
         s += if att.empty {
             "NULL"
         } else {
             "NOT NULL"
         };
-        if let Some(def) = &att.defval {
-            s += format!(" DEFAULT {}", match att.dtype {
-                FieldType::Txt => format!("\"{}\"", def),
-                _=>def.to_owned()
-            }).as_str()
+        if let Some(def) = self.default_sql(type_writer) {
+            s += format!(" DEFAULT {def}").as_str()
+        }
+        if let Some(comment) = &att.comment {
+            match type_writer.comment_style() {
+                CommentStyle::Inline => s += format!(" COMMENT {}", quote_comment(comment)).as_str(),
+                CommentStyle::SqlComment => s = sql_comment(comment, "\n  ") + s.as_str(),
+                CommentStyle::CommentOn => (),
+            }
         }
         s
     }
@@ -235,7 +563,7 @@ impl DBObject for Field {
 
 /// Vector of Field's
 pub type Fields = Vec<Field>;
-type FieldNames = Vec<String>;
+pub type FieldNames = Vec<String>;
 
 /// Types of GRANT permissions
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -249,48 +577,215 @@ pub enum GrantType {
     Trigger,
     All,
     Usage,
+    Execute,
 }
-impl ToString for GrantType {
-    fn to_string(&self) -> String {
-        match self {
-            GrantType::Select=>"SELECT".to_owned(),
-            GrantType::Insert=>"INSERT".to_owned(),
-            GrantType::Update=>"UPDATE".to_owned(),
-            GrantType::Delete=>"DELETE".to_owned(),
-            GrantType::Truncate=>"TRUNCATE".to_owned(),
-            GrantType::References=>"REFERENCES".to_owned(),
-            GrantType::Trigger=>"TRIGGER".to_owned(),
-            GrantType::All=>"ALL".to_owned(),
-            GrantType::Usage=>"USAGE".to_owned(),
+impl fmt::Display for GrantType {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            GrantType::Select=>"SELECT",
+            GrantType::Insert=>"INSERT",
+            GrantType::Update=>"UPDATE",
+            GrantType::Delete=>"DELETE",
+            GrantType::Truncate=>"TRUNCATE",
+            GrantType::References=>"REFERENCES",
+            GrantType::Trigger=>"TRIGGER",
+            GrantType::All=>"ALL",
+            GrantType::Usage=>"USAGE",
+            GrantType::Execute=>"EXECUTE",
+        })
+    }
+}
+
+/// (De)serialization of lists written as a single value when they have one element
+mod one_or_many {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        One(T),
+        Many(Vec<T>),
+    }
+
+    pub fn serialize<T:Serialize, S:Serializer>(values:&[T], serializer:S) -> Result<S::Ok, S::Error> {
+        match values {
+            [value] => value.serialize(serializer),
+            _ => values.serialize(serializer),
         }
     }
+    pub fn deserialize<'de, T:Deserialize<'de>, D:Deserializer<'de>>(deserializer:D) -> Result<Vec<T>, D::Error> {
+        Ok(match OneOrMany::deserialize(deserializer)? {
+            OneOrMany::One(value) => vec![value],
+            OneOrMany::Many(values) => values,
+        })
+    }
+}
+
+/// Text of GRANT and REVOKE clauses from the privileges to the object they are on
+fn privileges_clause(permissions:&[GrantType], columns:&FieldNames, on:&ObjectPath, all_tables:bool, type_writer:&dyn TypeWriter) -> String {
+    let cols = if columns.is_empty() { "".to_owned() } else { format!(" ({})", columns.join(", ")) };
+    let privileges:Vec<String> = permissions.iter().map(|p| format!("{p}{cols}")).collect();
+    let target = if all_tables {
+        type_writer.all_tables_in_schema(&on.name)
+    } else {
+        format!("{} {}", on.otype.grant_target(), type_writer.schema(on))
+    };
+    format!("{} ON {target}", privileges.join(", "))
+}
+
+/// GRANTED BY clause, if the TypeWriter supports it
+fn granted_by_clause(granted_by:&Option<String>, type_writer:&dyn TypeWriter) -> String {
+    match granted_by {
+        Some(grantor) if type_writer.supports_granted_by() => format!(" GRANTED BY {grantor}"),
+        _ => "".to_owned(),
+    }
 }
 
 /// GRANT generator
+///
+/// permission and to take a single value or a list. With all_tables, on is a Schema and
+/// the permissions are granted on the Tables in it (the ones existing when it is run)
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Grant {
-    pub permission: GrantType,
-    pub to: String,
+    #[serde(rename = "permission", alias = "permissions", with = "one_or_many")]
+    pub permissions: Vec<GrantType>,
+    #[serde(with = "one_or_many")]
+    pub to: Vec<String>,
     pub on: ObjectPath,
+    /// Fields the permissions are limited to (all of them if empty)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub columns: FieldNames,
+    #[serde(default="default_false")]
+    #[serde(skip_serializing_if = "is_default_false")]
+    pub all_tables: bool,
+    /// Let to grant the permission to other roles
+    #[serde(default="default_false")]
+    #[serde(skip_serializing_if = "is_default_false")]
+    pub with_grant_option: bool,
+    /// Role the permission is granted as (instead of the current one)
+    #[serde(default, skip_serializing_if = "is_none")]
+    pub granted_by: Option<String>,
 }
 impl Grant {
     /// Create a GRANT sepecifying permissions, grantee and affected object
     pub fn new(perm:GrantType, to:&str, on:&ObjectPath) -> Self {
-        Grant { permission: perm.to_owned(), to: to.to_string(), on: on.to_owned() }
+        Grant::new_many(vec![perm], &[to], on)
+    }
+    /// Create a GRANT of many permissions to many grantees
+    pub fn new_many(perms:Vec<GrantType>, to:&[&str], on:&ObjectPath) -> Self {
+        Grant {
+            permissions: perms,
+            to: to.iter().map(|r| r.to_string()).collect(),
+            on: on.to_owned(),
+            columns: Vec::new(),
+            all_tables: false,
+            with_grant_option: false,
+            granted_by: None,
+        }
+    }
+    /// Get the Revoke undoing this Grant
+    pub fn revoke(&self) -> Revoke {
+        Revoke {
+            permissions: self.permissions.to_owned(),
+            from: self.to.to_owned(),
+            on: self.on.to_owned(),
+            columns: self.columns.to_owned(),
+            all_tables: self.all_tables,
+            grant_option_for: false,
+            granted_by: self.granted_by.to_owned(),
+            cascade: false,
+        }
     }
 }
 #[typetag::serde]
 impl DBObject for Grant {
     fn to_sql(&self, type_writer:&dyn TypeWriter) -> String {
         let mut rv = "".to_owned();
-        if type_writer.supports_permissions() {
-            if self.on.otype != ObjectType::Sequence || type_writer.supports_sequences() {
-                rv = format!("GRANT {} ON {} {} TO {};", self.permission.to_string(), self.on.otype.to_string(), type_writer.schema(&self.on), self.to)
-            }
+        if type_writer.supports_permissions()
+            && supports_object_type(type_writer, &self.on.otype) {
+            rv = format!("GRANT {} TO {}{}{};",
+                privileges_clause(&self.permissions, &self.columns, &self.on, self.all_tables, type_writer),
+                self.to.join(", "),
+                if self.with_grant_option { " WITH GRANT OPTION" } else { "" },
+                granted_by_clause(&self.granted_by, type_writer))
         }
         rv
     }
+    fn drop_sql(&self, type_writer:&dyn TypeWriter) -> String {
+        self.revoke().to_sql(type_writer)
+    }
     fn is_top_level(&self) -> bool { true }
+    fn depends_on(&self) -> Vec<ObjectPath> { vec![self.on.to_owned()] }
+    fn top_level_to_sql(&self, type_writer:&dyn TypeWriter, _delayed: &Vec<&Box<& dyn DBObject>>) -> String {
+        self.to_sql(type_writer)
+    }
+}
+
+/// REVOKE generator
+///
+/// Like Grant, permission and from take a single value or a list
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Revoke {
+    #[serde(rename = "permission", alias = "permissions", with = "one_or_many")]
+    pub permissions: Vec<GrantType>,
+    #[serde(with = "one_or_many")]
+    pub from: Vec<String>,
+    pub on: ObjectPath,
+    /// Fields the permissions are limited to (all of them if empty)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub columns: FieldNames,
+    #[serde(default="default_false")]
+    #[serde(skip_serializing_if = "is_default_false")]
+    pub all_tables: bool,
+    /// Revoke only the right to grant the permission (GRANT OPTION FOR)
+    #[serde(default="default_false")]
+    #[serde(skip_serializing_if = "is_default_false")]
+    pub grant_option_for: bool,
+    /// Role the permission was granted as (instead of the current one)
+    #[serde(default, skip_serializing_if = "is_none")]
+    pub granted_by: Option<String>,
+    /// Revoke it from the roles it was granted to by from too
+    #[serde(default="default_false")]
+    #[serde(skip_serializing_if = "is_default_false")]
+    pub cascade: bool,
+}
+impl Revoke {
+    /// Create a REVOKE specifying permissions, role and affected object
+    pub fn new(perm:GrantType, from:&str, on:&ObjectPath) -> Self {
+        Grant::new(perm, from, on).revoke()
+    }
+    /// Get the Grant undoing this Revoke
+    pub fn grant(&self) -> Grant {
+        Grant {
+            permissions: self.permissions.to_owned(),
+            to: self.from.to_owned(),
+            on: self.on.to_owned(),
+            columns: self.columns.to_owned(),
+            all_tables: self.all_tables,
+            with_grant_option: self.grant_option_for,
+            granted_by: self.granted_by.to_owned(),
+        }
+    }
+}
+#[typetag::serde]
+impl DBObject for Revoke {
+    fn to_sql(&self, type_writer:&dyn TypeWriter) -> String {
+        if type_writer.supports_permissions()
+            && supports_object_type(type_writer, &self.on.otype) {
+            format!("REVOKE {}{} FROM {}{}{};", if self.grant_option_for { "GRANT OPTION FOR " } else { "" },
+                privileges_clause(&self.permissions, &self.columns, &self.on, self.all_tables, type_writer),
+                self.from.join(", "),
+                granted_by_clause(&self.granted_by, type_writer),
+                if self.cascade { " CASCADE" } else { "" })
+        } else {
+            "".to_owned()
+        }
+    }
+    fn drop_sql(&self, type_writer:&dyn TypeWriter) -> String {
+        self.grant().to_sql(type_writer)
+    }
+    fn is_top_level(&self) -> bool { true }
+    fn depends_on(&self) -> Vec<ObjectPath> { vec![self.on.to_owned()] }
     fn top_level_to_sql(&self, type_writer:&dyn TypeWriter, _delayed: &Vec<&Box<& dyn DBObject>>) -> String {
         self.to_sql(type_writer)
     }
@@ -312,21 +807,21 @@ impl Owner {
 impl DBObject for Owner {
     fn to_sql(&self, type_writer:&dyn TypeWriter) -> String {
         let mut rv = "".to_owned();
-        if type_writer.supports_permissions() {
-            if self.of.otype != ObjectType::Sequence || type_writer.supports_sequences() {
-                rv = format!("ALTER {} {} OWNER TO {};", self.of.otype.to_string(), type_writer.schema(&self.of), self.to)
-            }
+        if type_writer.supports_permissions()
+            && supports_object_type(type_writer, &self.of.otype) {
+            rv = format!("ALTER {} {} OWNER TO {};", self.of.otype, type_writer.schema(&self.of), self.to)
         }
         rv
     }
     fn is_top_level(&self) -> bool { true }
+    fn depends_on(&self) -> Vec<ObjectPath> { vec![self.of.to_owned()] }
     fn top_level_to_sql(&self, type_writer:&dyn TypeWriter, _delayed: &Vec<&Box<& dyn DBObject>>) -> String {
         self.to_sql(type_writer)
     }
 }
 
 /// INDEX generator
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Index {
     pub table: ObjectPath,
     pub fields: FieldNames,
@@ -338,18 +833,36 @@ impl Index {
             fields:fields.to_owned(),
         }
     }
+    /// Get the name of this Index
+    pub fn name(&self) -> String {
+        format!("{}_{}_idx", self.table.name, self.fields.join("_"))
+    }
+    fn create_sql(&self, type_writer:&dyn TypeWriter, if_not_exists:bool) -> String {
+        format!("CREATE INDEX {}{} ON {}{} ({});",
+                if if_not_exists { "IF NOT EXISTS " } else { "" },
+                self.name(),
+                type_writer.schema(&self.table),
+                type_writer.index_type(),
+                self.fields.join(","))
+    }
 }
 #[typetag::serde]
 impl DBObject for Index {
     fn to_sql(&self, type_writer:&dyn TypeWriter) -> String {
-        format!("CREATE INDEX {}_{}_idx ON {}{} ({});",
-                self.table.name,
-                self.fields.join("_"),
-                type_writer.schema(&self.table),
-                type_writer.index_type(),
-                self.fields.join(","))
+        self.create_sql(type_writer, false)
+    }
+    fn drop_sql(&self, type_writer:&dyn TypeWriter) -> String {
+        let path = ObjectPath { schema: self.table.schema.to_owned(), name: self.name(), otype: ObjectType::Table };
+        match type_writer.alter_style() {
+            AlterStyle::Modify => format!("DROP INDEX {} ON {};", self.name(), type_writer.schema(&self.table)),
+            _ => format!("DROP INDEX {};", type_writer.schema(&path)),
+        }
     }
 	fn is_top_level(&self) -> bool { true }
+    fn depends_on(&self) -> Vec<ObjectPath> { vec![self.table.to_owned()] }
+    fn if_not_exists_sql(&self, type_writer:&dyn TypeWriter, _delayed: &Vec<&Box<& dyn DBObject>>) -> String {
+        self.create_sql(type_writer, type_writer.supports_index_if_not_exists())
+    }
 }
 
 type Indexes = Vec<Index>;
@@ -381,17 +894,28 @@ impl DBObject for PrimaryKey {
 }
 
 /// Types of ForeignKey ON clause
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum FKOn {
+    #[serde(alias = "restrict")]
     Restrict,
+    #[serde(alias = "cascade")]
     Cascade,
+    #[serde(alias = "set_null")]
+    SetNull,
+    #[serde(alias = "set_default")]
+    SetDefault,
+    #[serde(alias = "no_action")]
+    NoAction,
 }
-impl ToString for FKOn {
-    fn to_string(&self) -> String {
-        match self {
-            FKOn::Cascade=>"CASCADE".to_owned(),
-            FKOn::Restrict=>"RESTRICT".to_owned(),
-        }
+impl fmt::Display for FKOn {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            FKOn::Cascade=>"CASCADE",
+            FKOn::Restrict=>"RESTRICT",
+            FKOn::SetNull=>"SET NULL",
+            FKOn::SetDefault=>"SET DEFAULT",
+            FKOn::NoAction=>"NO ACTION",
+        })
     }
 }
 
@@ -399,8 +923,23 @@ fn default_on_clause() -> FKOn {
     FKOn::Restrict
 }
 
+/// Types of ForeignKey MATCH clause
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum FKMatch {
+    Full,
+    Simple,
+}
+impl fmt::Display for FKMatch {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            FKMatch::Full=>"FULL",
+            FKMatch::Simple=>"SIMPLE",
+        })
+    }
+}
+
 /// ForeignKey generator
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ForeignKey {
     pub table: ObjectPath,
     pub fields: FieldNames,
@@ -410,48 +949,153 @@ pub struct ForeignKey {
     pub on_delete: FKOn,
     #[serde(default="default_on_clause")]
     pub on_update: FKOn,
+    /// Can the check be deferred until the end of the transaction?
+    #[serde(default="default_false")]
+    #[serde(skip_serializing_if = "is_default_false")]
+    pub deferrable: bool,
+    /// Is the check deferred by default? (implies deferrable)
+    #[serde(default="default_false")]
+    #[serde(skip_serializing_if = "is_default_false")]
+    pub initially_deferred: bool,
+    /// Optional MATCH clause
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub match_type: Option<FKMatch>,
+    /// Add it to an existing Table without checking the rows already there
+    /// (only when altering the Table, the rows are checked later by validate_sql())
+    #[serde(default="default_false")]
+    #[serde(skip_serializing_if = "is_default_false")]
+    pub not_valid: bool,
+}
+impl ForeignKey {
+    /// Create a ForeignKey with the default ON clauses (RESTRICT)
+    pub fn new(table:&ObjectPath, fields:&FieldNames, ref_table:&ObjectPath, ref_fields:&FieldNames) -> Self {
+        ForeignKey {
+            table: table.to_owned(),
+            fields: fields.to_owned(),
+            ref_table: ref_table.to_owned(),
+            ref_fields: ref_fields.to_owned(),
+            on_delete: default_on_clause(),
+            on_update: default_on_clause(),
+            deferrable: false,
+            initially_deferred: false,
+            match_type: None,
+            not_valid: false,
+        }
+    }
+    /// Get the constraint name of this ForeignKey
+    pub fn name(&self) -> String {
+        format!("{}_{}_{}_fk", self.table.name, self.ref_table.name, self.fields.join("_"))
+    }
+    /// Get the statement checking the existing rows of a ForeignKey added NOT VALID
+    pub fn validate_sql(&self, type_writer:&dyn TypeWriter) -> String {
+        if self.not_valid && type_writer.supports_not_valid() {
+            format!("ALTER TABLE {} VALIDATE CONSTRAINT {};", type_writer.schema(&self.table), self.name())
+        } else {
+            "".to_owned()
+        }
+    }
+    /// Is SET NULL used in any of the ON clauses?
+    pub fn sets_null(&self) -> bool {
+        self.on_delete == FKOn::SetNull || self.on_update == FKOn::SetNull
+    }
+    /// Get the clauses this TypeWriter can not render (they are dropped by to_sql())
+    pub fn unsupported_clauses(&self, type_writer:&dyn TypeWriter) -> Vec<String> {
+        let mut rv = Vec::new();
+        if ! type_writer.supports_fk_action(&self.on_delete) {
+            rv.push(format!("ON DELETE {}", self.on_delete))
+        }
+        if ! type_writer.supports_fk_action(&self.on_update) {
+            rv.push(format!("ON UPDATE {}", self.on_update))
+        }
+        if let Some(mt) = &self.match_type {
+            if ! type_writer.supports_fk_match() {
+                rv.push(format!("MATCH {mt}"))
+            }
+        }
+        if (self.deferrable || self.initially_deferred) && ! type_writer.supports_deferrable() {
+            rv.push("DEFERRABLE".to_owned())
+        }
+        rv
+    }
 }
 
 #[typetag::serde]
 impl DBObject for ForeignKey {
     fn to_sql(&self, type_writer:&dyn TypeWriter) -> String {
-        let (prefix, end, sep) = ("".to_string(), "", "");
-        format!("{prefix}CONSTRAINT {}_{}_{}_fk{sep} FOREIGN KEY ({}){sep} REFERENCES {} ({}){sep} ON DELETE {} ON UPDATE {}{end}",
-                self.table.name, self.ref_table.name, self.fields.join("_"),
+        let mut s = format!("CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({})",
+                self.name(),
                 self.fields.join(","),
                 type_writer.schema(&self.ref_table),
-                self.ref_fields.join(","),
-                self.on_delete.to_string(),
-                self.on_update.to_string()
-        )
+                self.ref_fields.join(","));
+        if let Some(mt) = &self.match_type {
+            if type_writer.supports_fk_match() {
+                s += format!(" MATCH {mt}").as_str()
+            }
+        }
+        if type_writer.supports_fk_action(&self.on_delete) {
+            s += format!(" ON DELETE {}", self.on_delete).as_str()
+        }
+        if type_writer.supports_fk_action(&self.on_update) {
+            s += format!(" ON UPDATE {}", self.on_update).as_str()
+        }
+        if (self.deferrable || self.initially_deferred) && type_writer.supports_deferrable() {
+            s += " DEFERRABLE";
+            if self.initially_deferred {
+                s += " INITIALLY DEFERRED"
+            }
+        }
+        s
     }
 }
 
 /// Types of upper-level objects
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
 pub enum ObjectType {
+    #[default]
     Table,
     Sequence,
     Schema,
+    View,
+    MaterializedView,
+    Function,
+    Procedure,
 }
-impl ToString for ObjectType {
-    fn to_string(&self) -> String {
+impl fmt::Display for ObjectType {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            ObjectType::Table=>"TABLE",
+            ObjectType::Sequence=>"SEQUENCE",
+            ObjectType::Schema=>"SCHEMA",
+            ObjectType::View=>"VIEW",
+            ObjectType::MaterializedView=>"MATERIALIZED VIEW",
+            ObjectType::Function=>"FUNCTION",
+            ObjectType::Procedure=>"PROCEDURE",
+        })
+    }
+}
+impl ObjectType {
+    /// Get the object type used by GRANT (views are granted as tables)
+    pub fn grant_target(&self) -> String {
         match self {
-            ObjectType::Table=>"TABLE".to_owned(),
-            ObjectType::Sequence=>"SEQUENCE".to_owned(),
-            ObjectType::Schema=>"SCHEMA".to_owned(),
+            ObjectType::View | ObjectType::MaterializedView=>ObjectType::Table.to_string(),
+            _=>self.to_string(),
         }
     }
 }
-impl Default for ObjectType {
-    fn default() -> Self {
-        ObjectType::Table
+
+/// Can this TypeWriter create objects of this type?
+fn supports_object_type(type_writer:&dyn TypeWriter, otype:&ObjectType) -> bool {
+    match otype {
+        ObjectType::Sequence=>type_writer.supports_sequences(),
+        ObjectType::MaterializedView=>type_writer.supports_materialized_views(),
+        ObjectType::Function | ObjectType::Procedure=>type_writer.supports_functions(),
+        _=>true,
     }
 }
 
 
 /// Path of an object
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct ObjectPath {
     pub schema: Option<String>,
     pub name: String,
@@ -467,10 +1111,33 @@ impl ObjectPath {
     pub fn new_table_only(name:&str) -> Self {
         ObjectPath { schema: None, name: name.to_string(), otype:ObjectType::Schema }
     }
+    /// Create ObjectPath of Table from its full name ("schema.table" or "table")
+    pub fn new_table_from_full_name(full_name:&str) -> Self {
+        match full_name.split_once('.') {
+            Some((schema, name)) => ObjectPath::new_table(schema, name),
+            None => ObjectPath { schema: None, name: full_name.to_string(), otype:ObjectType::Table },
+        }
+    }
     /// Create ObjectPath of a Sequence
     pub fn new_sequence(schema:&str, name:&str) -> Self {
         ObjectPath { schema: Some(schema.to_string()), name: name.to_string(), otype:ObjectType::Sequence }
     }
+    /// Create ObjectPath of a View
+    pub fn new_view(schema:&str, name:&str) -> Self {
+        ObjectPath { schema: Some(schema.to_string()), name: name.to_string(), otype:ObjectType::View }
+    }
+    /// Create ObjectPath of a MaterializedView
+    pub fn new_materialized_view(schema:&str, name:&str) -> Self {
+        ObjectPath { schema: Some(schema.to_string()), name: name.to_string(), otype:ObjectType::MaterializedView }
+    }
+    /// Create ObjectPath of a Function
+    pub fn new_function(schema:&str, name:&str) -> Self {
+        ObjectPath { schema: Some(schema.to_string()), name: name.to_string(), otype:ObjectType::Function }
+    }
+    /// Create ObjectPath of a (stored) Procedure
+    pub fn new_procedure(schema:&str, name:&str) -> Self {
+        ObjectPath { schema: Some(schema.to_string()), name: name.to_string(), otype:ObjectType::Procedure }
+    }
     /// Get the full name of this ObjectPath
     pub fn full_name(&self) -> String {
         if let Some(schema) = &self.schema {
@@ -490,18 +1157,30 @@ impl ObjectPath {
         } else {
             ""
         };
-        return my_schema == other_schema
+        my_schema == other_schema
             && self.name == other.name
             && self.otype == other.otype
     }
 }
 
+fn field_references(path:&ObjectPath, fields:&Fields) -> ForeignKeys {
+    fields.iter()
+        .filter_map(|f| f.attributes.references.as_ref().map(|r| r.to_foreign_key(path, &f.name)))
+        .collect()
+}
+
 /// TABLE generator
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Table {
     pub path: ObjectPath,
     pub fields: Fields,
     pub fks: Option<ForeignKeys>,
+    /// Names of the Mixin's to pull into this Table (resolved by the Loader)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mixins: Vec<String>,
+    /// Optional documentation for this table
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "description")]
+    pub comment: Option<String>,
 }
 impl Table {
     /// Create a table with ObjectPath and Fields
@@ -518,11 +1197,13 @@ impl Table {
             if ! dups.is_empty() {                
                 panic!("{} has duplicated fields: {dups:?}", path.full_name())
             }
+            let mut all_fks = field_references(path, &fields);
             if let Some(thefks) = &fks {
-                for fk in thefks.iter() {
-                    if ! path.is_equal(&fk.table) {
-                        panic!("{} is not a valid fk for {}", fk.table.full_name(), path.full_name())
-                    }
+                all_fks.extend(thefks.iter().cloned());
+            }
+            for fk in all_fks.iter() {
+                if ! path.is_equal(&fk.table) {
+                    panic!("{} is not a valid fk for {}", fk.table.full_name(), path.full_name())
                 }
             }
         }
@@ -530,7 +1211,35 @@ impl Table {
             path: path.to_owned(),
             fields,
             fks,
+            mixins: Vec::new(),
+            comment: None,
+        }
+    }
+    /// Check that the ForeignKeys of this Table can be created: SET NULL actions need nullable fields
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        for fk in self.foreign_keys().iter().filter(|fk| fk.sets_null()) {
+            if let Some(f) = self.fields.iter().find(|f| fk.fields.contains(&f.name) && ! f.attributes.empty) {
+                return Err(format!("{} uses SET NULL on NOT NULL field {}.{}", fk.name(), self.path.full_name(), f.name).into())
+            }
+        }
+        Ok(())
+    }
+    /// Get the warnings about the parts of this Table some TypeWriters can not write
+    pub fn warnings(&self) -> Vec<String> {
+        let mut rv = Vec::new();
+        if self.fields.iter().filter(|f| f.attributes.dtype == FieldType::AutoInc).count() > 1 {
+            rv.push(format!("{} has more than one AutoInc field, rejected by Mysql and Sqlite", self.path.full_name()))
+        }
+        rv
+    }
+    /// Get the ForeignKeys of this Table, including the ones declared with FieldAttributes references
+    pub fn foreign_keys(&self) -> ForeignKeys {
+        let mut fks = Vec::new();
+        if let Some(thefks) = &self.fks {
+            fks.extend(thefks.iter().cloned());
         }
+        fks.extend(field_references(&self.path, &self.fields));
+        fks
     }
     /// Get the indexed fields in this Table, if any
     pub fn indexes(&self) -> Option<Indexes> {
@@ -546,16 +1255,22 @@ impl Table {
             None
         }
     }
-    fn gen_sql(&self, type_writer:&dyn TypeWriter, extras:Option<&Vec<String>>) -> String {
+    fn gen_sql(&self, type_writer:&dyn TypeWriter, extras:Option<&Vec<String>>, if_not_exists:bool) -> String {
         let cols : Vec<String> = self.fields.iter().map(|f| f.to_sql(type_writer).to_owned()).collect();
         let mut cts:Vec<Box<dyn DBObject>> = Vec::new();
         let mut uks:Vec<String> = Vec::new();
         let mut pks:Vec<String> = Vec::new();
+        // the AutoInc field is the PRIMARY KEY, the other ones are kept UNIQUE
+        let inline_pk = type_writer.auto_increment_is_primary_key()
+            && self.fields.iter().any(|f| f.attributes.dtype == FieldType::AutoInc);
+        let mut pk_uks:Vec<String> = Vec::new();
         for f in self.fields.iter() {
             if f.attributes.primary_key {
-                if type_writer.supports_auto_increment() || f.attributes.dtype != FieldType::AutoInc {
+                if ! inline_pk {
                     pks.push(f.name.to_owned())
-                }                
+                } else if f.attributes.dtype != FieldType::AutoInc {
+                    pk_uks.push(f.name.to_owned())
+                }
             }
             if f.attributes.unique {
                 uks.push(f.name.to_owned())
@@ -564,35 +1279,43 @@ impl Table {
         if ! pks.is_empty() {
             cts.push(Box::new(PrimaryKey{ name: format!("{}_{}", self.path.name, pks.join("_")), fields:pks }))
         }
+        if ! pk_uks.is_empty() {
+            cts.push(Box::new(UniqueKey{ name: format!("{}_{}", self.path.name, pk_uks.join("_")), fields:pk_uks}))
+        }
         if ! uks.is_empty() {
             cts.push(Box::new(UniqueKey{ name: format!("{}_{}", self.path.name, uks.join("_")), fields:uks}))
         }
         let mut refs : Vec<String> = cts.iter().map(|f| f.to_sql(type_writer).to_owned()).collect();
-        if let Some(fks) = &self.fks {
-           for fk in fks.iter() {
-               refs.push(fk.to_sql(type_writer))
-           }
+        for fk in self.foreign_keys().iter() {
+            refs.push(fk.to_sql(type_writer))
         }
         let exts = if let Some(ext) = extras {
             format!(",\n  {}", ext.join(",\n  "))
         } else {
             "".to_owned()
         };
-        let mut t=format!("CREATE TABLE {} (\n  {}{}", type_writer.schema(&self.path), cols.join(",\n  "), exts);
+        let style = type_writer.comment_style();
+        let mut t = match (&self.comment, &style) {
+            (Some(comment), CommentStyle::SqlComment) => sql_comment(comment, "\n"),
+            _ => "".to_owned(),
+        };
+        t += format!("CREATE TABLE {}{} (\n  {}{}", if if_not_exists { "IF NOT EXISTS " } else { "" },
+            type_writer.schema(&self.path), cols.join(",\n  "), exts).as_str();
         if ! refs.is_empty() {
             t += format!(",\n  {}", refs.join(",\n  ")).as_str()
         }
-        t += "\n);";
+        t += "\n)";
+        if let (Some(comment), CommentStyle::Inline) = (&self.comment, &style) {
+            t += format!(" COMMENT={}", quote_comment(comment)).as_str()
+        }
+        t += ";";
+        if style == CommentStyle::CommentOn {
+            t += self.comments_on(type_writer).as_str()
+        }
         t
     }
-}
-#[typetag::serde]
-impl DBObject for Table {
-    fn to_sql(&self, type_writer:&dyn TypeWriter) -> String {
-        self.gen_sql(type_writer, None)
-    }
-    fn is_top_level(&self) -> bool { true }
-    fn top_level_to_sql(&self, type_writer:&dyn TypeWriter, delayed: &Vec<&Box<& dyn DBObject>>) -> String {
+    #[allow(clippy::borrowed_box)]
+    fn delayed_sql(&self, type_writer:&dyn TypeWriter, delayed: &Vec<&Box<& dyn DBObject>>, if_not_exists:bool) -> String {
         let mut extras = Vec::new();
         for obj in delayed.iter() {
             let sql = obj.to_sql(type_writer);
@@ -602,11 +1325,112 @@ impl DBObject for Table {
             }
         }
         if extras.is_empty() {
-            self.gen_sql(type_writer, None)
+            self.gen_sql(type_writer, None, if_not_exists)
         } else {
-            self.gen_sql(type_writer, Some(&extras))
+            self.gen_sql(type_writer, Some(&extras), if_not_exists)
+        }
+    }
+    /// COMMENT ON statements for this Table and its Fields
+    fn comments_on(&self, type_writer:&dyn TypeWriter) -> String {
+        let table = type_writer.schema(&self.path);
+        let mut s = String::new();
+        if let Some(comment) = &self.comment {
+            s += format!("\nCOMMENT ON TABLE {table} IS {};", quote_comment(comment)).as_str()
+        }
+        for f in self.fields.iter() {
+            if let Some(comment) = &f.attributes.comment {
+                s += format!("\nCOMMENT ON COLUMN {table}.{} IS {};", f.quoted_name(), quote_comment(comment)).as_str()
+            }
+        }
+        s
+    }
+}
+#[typetag::serde]
+impl DBObject for Table {
+    fn to_sql(&self, type_writer:&dyn TypeWriter) -> String {
+        self.gen_sql(type_writer, None, false)
+    }
+    fn drop_sql(&self, type_writer:&dyn TypeWriter) -> String {
+        format!("DROP TABLE {};", type_writer.schema(&self.path))
+    }
+    fn is_top_level(&self) -> bool { true }
+    fn path(&self) -> Option<&ObjectPath> { Some(&self.path) }
+    fn depends_on(&self) -> Vec<ObjectPath> {
+        self.fields.iter().filter_map(|f| f.attributes.defval.as_deref().and_then(nextval_sequence)).collect()
+    }
+    fn top_level_to_sql(&self, type_writer:&dyn TypeWriter, delayed: &Vec<&Box<& dyn DBObject>>) -> String {
+        self.delayed_sql(type_writer, delayed, false)
+    }
+    fn if_not_exists_sql(&self, type_writer:&dyn TypeWriter, delayed: &Vec<&Box<& dyn DBObject>>) -> String {
+        self.delayed_sql(type_writer, delayed, type_writer.supports_if_not_exists())
+    }
+}
+
+/// Placeholder for the table name in the Triggers of a Mixin
+pub const MIXIN_TABLE: &str = "{table}";
+
+/// Named group of Fields, with their indexes and triggers, pulled into Tables
+///
+/// Fields are appended after the ones of the Table, in the order the mixins are listed.
+/// A Field already present is kept: the Table's own Fields win over the mixin ones,
+/// and earlier mixins win over later ones
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Mixin {
+    pub name: String,
+    pub fields: Fields,
+    /// Indexes as lists of field names
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub indexes: Vec<FieldNames>,
+    /// Triggers on the Table, "{table}" in their name, condition, body
+    /// and function name is replaced by the Table name
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub triggers: Vec<Trigger>,
+}
+impl Mixin {
+    /// Create a Mixin with Fields
+    pub fn new(name:&str, fields:Fields) -> Self {
+        Mixin {
+            name: name.to_string(),
+            fields,
+            indexes: Vec::new(),
+            triggers: Vec::new(),
         }
     }
+    /// Add the missing Fields to table, returning its Index'es and Trigger's
+    pub fn apply(&self, table:&mut Table) -> Vec<Box<dyn DBObject>> {
+        for f in self.fields.iter() {
+            if ! table.fields.iter().any(|tf| tf.name == f.name) {
+                table.fields.push(f.to_owned())
+            }
+        }
+        let mut objs:Vec<Box<dyn DBObject>> = Vec::new();
+        for idx in self.indexes.iter() {
+            objs.push(Box::new(Index::new(&table.path, idx)))
+        }
+        let tname = table.path.name.as_str();
+        for t in self.triggers.iter() {
+            let mut trigger = t.to_owned();
+            trigger.name = trigger.name.replace(MIXIN_TABLE, tname);
+            trigger.table = table.path.to_owned();
+            trigger.when = trigger.when.map(|w| w.replace(MIXIN_TABLE, tname));
+            trigger.body = trigger.body.map(|b| b.replace(MIXIN_TABLE, tname));
+            if let Some(function) = &mut trigger.function {
+                function.name = function.name.replace(MIXIN_TABLE, tname);
+            }
+            objs.push(Box::new(trigger))
+        }
+        objs
+    }
+}
+#[typetag::serde]
+impl DBObject for Mixin {
+    fn to_sql(&self, _type_writer:&dyn TypeWriter) -> String {
+        "".to_owned()
+    }
+    fn is_top_level(&self) -> bool { true }
+    fn top_level_to_sql(&self, _type_writer:&dyn TypeWriter, _delayed: &Vec<&Box<& dyn DBObject>>) -> String {
+        "".to_owned()
+    }
 }
 
 /// SCHEMA generator
@@ -620,12 +1444,22 @@ impl Schema {
     pub fn new(name:&str, owner:&str) -> Self {
         Schema{ name: name.to_string(), owner: owner.to_string(), }
     }
+    fn create_sql(&self, type_writer:&dyn TypeWriter, if_not_exists:bool) -> String {
+        if type_writer.supports_schemas() {
+            format!("CREATE SCHEMA {}{} AUTHORIZATION {};", if if_not_exists { "IF NOT EXISTS " } else { "" }, self.name, self.owner)
+        } else {
+            "".to_owned()
+        }
+    }
 }
 #[typetag::serde]
 impl DBObject for Schema {
     fn to_sql(&self, type_writer:&dyn TypeWriter) -> String {
+        self.create_sql(type_writer, false)
+    }
+    fn drop_sql(&self, type_writer:&dyn TypeWriter) -> String {
         if type_writer.supports_schemas() {
-            format!("CREATE SCHEMA {} AUTHORIZATION {};", self.name, self.owner)
+            format!("DROP SCHEMA {};", self.name)
         } else {
             "".to_owned()
         }
@@ -634,6 +1468,672 @@ impl DBObject for Schema {
     fn top_level_to_sql(&self, type_writer:&dyn TypeWriter, _delayed: &Vec<&Box<& dyn DBObject>>) -> String {
         self.to_sql(type_writer)
     }
+    fn if_not_exists_sql(&self, type_writer:&dyn TypeWriter, _delayed: &Vec<&Box<& dyn DBObject>>) -> String {
+        self.create_sql(type_writer, type_writer.supports_if_not_exists())
+    }
+}
+
+fn nextval(seq:&ObjectPath) -> String {
+    format!("nextval('{}')", seq.full_name())
+}
+
+fn is_nextval(defval:&str) -> bool {
+    defval.trim_start().to_lowercase().starts_with("nextval(")
+}
+
+/// Get the Sequence used by a nextval('schema.name') default value
+fn nextval_sequence(defval:&str) -> Option<ObjectPath> {
+    if ! is_nextval(defval) {
+        return None
+    }
+    let name = defval.split('\'').nth(1)?;
+    Some(match name.split_once('.') {
+        Some((schema, name)) => ObjectPath::new_sequence(schema, name),
+        None => ObjectPath { schema: None, name: name.to_string(), otype:ObjectType::Sequence },
+    })
+}
+
+/// Column owning a Sequence
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SequenceOwner {
+    pub table: ObjectPath,
+    pub field: String,
+}
+
+/// SEQUENCE generator
+///
+/// Only rendered by TypeWriters supporting sequences.
+/// owned_by is set by a separate ALTER SEQUENCE, once the owning table exists
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Sequence {
+    pub path: ObjectPath,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub increment: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_value: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_value: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<i64>,
+    #[serde(default="default_false")]
+    #[serde(skip_serializing_if = "is_default_false")]
+    pub cycle: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owned_by: Option<SequenceOwner>,
+}
+impl Sequence {
+    /// Create a Sequence with the database defaults
+    pub fn new(path:&ObjectPath) -> Self {
+        Sequence {
+            path: path.to_owned(),
+            start: None,
+            increment: None,
+            min_value: None,
+            max_value: None,
+            cache: None,
+            cycle: false,
+            owned_by: None,
+        }
+    }
+    /// Get the nextval() call for this Sequence, to be used as a Field default value
+    pub fn nextval(&self) -> String {
+        nextval(&self.path)
+    }
+}
+#[typetag::serde]
+impl DBObject for Sequence {
+    fn to_sql(&self, type_writer:&dyn TypeWriter) -> String {
+        if ! type_writer.supports_sequences() {
+            return "".to_owned()
+        }
+        let mut s = format!("CREATE SEQUENCE {}", type_writer.schema(&self.path));
+        if let Some(v) = self.increment {
+            s += format!(" INCREMENT BY {v}").as_str()
+        }
+        if let Some(v) = self.min_value {
+            s += format!(" MINVALUE {v}").as_str()
+        }
+        if let Some(v) = self.max_value {
+            s += format!(" MAXVALUE {v}").as_str()
+        }
+        if let Some(v) = self.start {
+            s += format!(" START WITH {v}").as_str()
+        }
+        if let Some(v) = self.cache {
+            s += format!(" CACHE {v}").as_str()
+        }
+        if self.cycle {
+            s += " CYCLE"
+        }
+        s + ";"
+    }
+    fn post_sql(&self, type_writer:&dyn TypeWriter) -> String {
+        match &self.owned_by {
+            Some(owner) if type_writer.supports_sequences() =>
+                format!("ALTER SEQUENCE {} OWNED BY {}.{};", type_writer.schema(&self.path), type_writer.schema(&owner.table), owner.field),
+            _ => "".to_owned(),
+        }
+    }
+    fn drop_sql(&self, type_writer:&dyn TypeWriter) -> String {
+        if type_writer.supports_sequences() {
+            format!("DROP SEQUENCE {};", type_writer.schema(&self.path))
+        } else {
+            "".to_owned()
+        }
+    }
+    fn is_top_level(&self) -> bool { true }
+    fn path(&self) -> Option<&ObjectPath> { Some(&self.path) }
+    fn top_level_to_sql(&self, type_writer:&dyn TypeWriter, _delayed: &Vec<&Box<& dyn DBObject>>) -> String {
+        self.to_sql(type_writer)
+    }
+}
+
+// otype is given by the kind of view, as the one of path defaults to Table when loaded
+fn view_drop(type_writer:&dyn TypeWriter, otype:ObjectType, path:&ObjectPath) -> String {
+    format!("DROP {otype} {};", type_writer.schema(path))
+}
+
+fn view_head(type_writer:&dyn TypeWriter, otype:ObjectType, path:&ObjectPath, columns:&Option<FieldNames>) -> String {
+    let mut s = format!("CREATE {otype} {}", type_writer.schema(path));
+    if let Some(cols) = columns {
+        s += format!(" ({})", cols.join(",")).as_str()
+    }
+    s
+}
+
+/// VIEW generator
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct View {
+    pub path: ObjectPath,
+    /// Optional names for the columns of the query
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns: Option<FieldNames>,
+    /// SQL body (SELECT ...)
+    pub query: String,
+    /// Add WITH CHECK OPTION?
+    #[serde(default="default_false")]
+    #[serde(skip_serializing_if = "is_default_false")]
+    pub check_option: bool,
+    /// Objects used by the query
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<ObjectPath>,
+}
+impl View {
+    /// Create a View on path with a query
+    pub fn new(path:&ObjectPath, query:&str) -> Self {
+        let mut path = path.to_owned();
+        path.otype = ObjectType::View;
+        View { path, columns: None, query: query.to_string(), check_option: false, depends_on: Vec::new() }
+    }
+}
+#[typetag::serde]
+impl DBObject for View {
+    fn to_sql(&self, type_writer:&dyn TypeWriter) -> String {
+        let mut s = format!("{} AS\n{}", view_head(type_writer, ObjectType::View, &self.path, &self.columns), self.query.trim().trim_end_matches(';'));
+        if self.check_option && type_writer.supports_view_check_option() {
+            s += "\nWITH CHECK OPTION"
+        }
+        s + ";"
+    }
+    fn drop_sql(&self, type_writer:&dyn TypeWriter) -> String {
+        view_drop(type_writer, ObjectType::View, &self.path)
+    }
+    fn is_top_level(&self) -> bool { true }
+    fn path(&self) -> Option<&ObjectPath> { Some(&self.path) }
+    fn depends_on(&self) -> Vec<ObjectPath> { self.depends_on.to_owned() }
+    fn top_level_to_sql(&self, type_writer:&dyn TypeWriter, _delayed: &Vec<&Box<& dyn DBObject>>) -> String {
+        self.to_sql(type_writer)
+    }
+}
+
+/// MATERIALIZED VIEW generator
+///
+/// Only rendered by TypeWriters supporting materialized views
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MaterializedView {
+    pub path: ObjectPath,
+    /// Optional names for the columns of the query
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns: Option<FieldNames>,
+    /// SQL body (SELECT ...)
+    pub query: String,
+    /// Populate it when created?
+    #[serde(default="default_true")]
+    #[serde(skip_serializing_if = "is_default_true")]
+    pub with_data: bool,
+    /// Fields of the UNIQUE index allowing REFRESH ... CONCURRENTLY
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unique_index: Option<FieldNames>,
+    /// Objects used by the query
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<ObjectPath>,
+}
+impl MaterializedView {
+    /// Create a MaterializedView on path with a query
+    pub fn new(path:&ObjectPath, query:&str) -> Self {
+        let mut path = path.to_owned();
+        path.otype = ObjectType::MaterializedView;
+        MaterializedView { path, columns: None, query: query.to_string(), with_data: true, unique_index: None, depends_on: Vec::new() }
+    }
+    /// Get the REFRESH statement for this MaterializedView (CONCURRENTLY if it has a unique index)
+    pub fn refresh_sql(&self, type_writer:&dyn TypeWriter) -> String {
+        if ! type_writer.supports_materialized_views() {
+            return "".to_owned()
+        }
+        let concurrently = if self.unique_index.is_some() { " CONCURRENTLY" } else { "" };
+        format!("REFRESH MATERIALIZED VIEW{} {};", concurrently, type_writer.schema(&self.path))
+    }
+}
+#[typetag::serde]
+impl DBObject for MaterializedView {
+    fn to_sql(&self, type_writer:&dyn TypeWriter) -> String {
+        if ! type_writer.supports_materialized_views() {
+            return "".to_owned()
+        }
+        let mut s = format!("{} AS\n{}", view_head(type_writer, ObjectType::MaterializedView, &self.path, &self.columns), self.query.trim().trim_end_matches(';'));
+        if ! self.with_data {
+            s += "\nWITH NO DATA"
+        }
+        s += ";";
+        if let Some(fields) = &self.unique_index {
+            s += format!("\nCREATE UNIQUE INDEX {}_{}_idx ON {} ({});",
+                self.path.name, fields.join("_"), type_writer.schema(&self.path), fields.join(",")).as_str()
+        }
+        s
+    }
+    fn drop_sql(&self, type_writer:&dyn TypeWriter) -> String {
+        if type_writer.supports_materialized_views() {
+            view_drop(type_writer, ObjectType::MaterializedView, &self.path)
+        } else {
+            "".to_owned()
+        }
+    }
+    fn is_top_level(&self) -> bool { true }
+    fn path(&self) -> Option<&ObjectPath> { Some(&self.path) }
+    fn depends_on(&self) -> Vec<ObjectPath> { self.depends_on.to_owned() }
+    fn top_level_to_sql(&self, type_writer:&dyn TypeWriter, _delayed: &Vec<&Box<& dyn DBObject>>) -> String {
+        self.to_sql(type_writer)
+    }
+}
+
+/// Mode of a Function argument
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub enum ArgMode {
+    In,
+    Out,
+    InOut,
+}
+impl fmt::Display for ArgMode {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            ArgMode::In=>"IN",
+            ArgMode::Out=>"OUT",
+            ArgMode::InOut=>"INOUT",
+        })
+    }
+}
+
+/// Argument of a Function
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FunctionArg {
+    pub name: String,
+    /// Native SQL type of the argument
+    #[serde(rename = "type")]
+    pub dtype: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<ArgMode>,
+    /// Optional default value (dropped where unsupported)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+impl FunctionArg {
+    /// Create an argument named name of (native SQL) type dtype
+    pub fn new(name:&str, dtype:&str) -> Self {
+        FunctionArg { name: name.to_string(), dtype: dtype.to_string(), mode: None, default: None }
+    }
+}
+
+/// Volatility of a Function
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub enum Volatility {
+    Volatile,
+    Stable,
+    Immutable,
+}
+impl fmt::Display for Volatility {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Volatility::Volatile=>"VOLATILE",
+            Volatility::Stable=>"STABLE",
+            Volatility::Immutable=>"IMMUTABLE",
+        })
+    }
+}
+
+fn default_language() -> String {
+    "plpgsql".to_owned()
+}
+
+fn is_default_language(lang:&String) -> bool {
+    *lang == default_language()
+}
+
+/// Deserialize the path of a Function, its ObjectType being Function when not given
+fn routine_path<'de, D:Deserializer<'de>>(deserializer:D) -> Result<ObjectPath, D::Error> {
+    let mut path = ObjectPath::deserialize(deserializer)?;
+    if path.otype == ObjectType::Table {
+        path.otype = ObjectType::Function
+    }
+    Ok(path)
+}
+
+/// FUNCTION (or PROCEDURE) generator
+///
+/// The body is written in the dialect of the TypeWriter, dml-tools does not translate it
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Function {
+    #[serde(deserialize_with = "routine_path")]
+    pub path: ObjectPath,
+    /// Write a PROCEDURE instead of a FUNCTION?
+    #[serde(default="default_false")]
+    #[serde(skip_serializing_if = "is_default_false")]
+    pub procedure: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<FunctionArg>,
+    /// Native SQL return type (ignored for procedures)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub returns: Option<String>,
+    #[serde(default="default_language")]
+    #[serde(skip_serializing_if = "is_default_language")]
+    pub language: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volatility: Option<Volatility>,
+    #[serde(default="default_false")]
+    #[serde(skip_serializing_if = "is_default_false")]
+    pub security_definer: bool,
+    pub body: String,
+    /// Objects used by the body
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<ObjectPath>,
+}
+impl Function {
+    /// Create a Function (or Procedure, for paths of ObjectType Procedure) on path with a body
+    pub fn new(path:&ObjectPath, returns:Option<&str>, body:&str) -> Self {
+        let procedure = path.otype == ObjectType::Procedure;
+        let mut path = path.to_owned();
+        path.otype = if procedure { ObjectType::Procedure } else { ObjectType::Function };
+        Function {
+            path,
+            procedure,
+            args: Vec::new(),
+            returns: returns.map(|r| r.to_string()),
+            language: default_language(),
+            volatility: None,
+            security_definer: false,
+            body: body.to_string(),
+            depends_on: Vec::new(),
+        }
+    }
+    /// Is it a PROCEDURE?
+    pub fn is_procedure(&self) -> bool {
+        self.procedure
+    }
+    fn kind(&self) -> ObjectType {
+        if self.procedure { ObjectType::Procedure } else { ObjectType::Function }
+    }
+    fn args_sql(&self, with_defaults:bool) -> String {
+        let args:Vec<String> = self.args.iter().map(|a| {
+            let mut s = match &a.mode {
+                Some(mode) => format!("{} {} {}", mode, a.name, a.dtype),
+                None => format!("{} {}", a.name, a.dtype),
+            };
+            if let Some(def) = a.default.as_ref().filter(|_| with_defaults) {
+                s += format!(" DEFAULT {def}").as_str()
+            }
+            s
+        }).collect();
+        args.join(", ")
+    }
+    fn dollar_quoted_sql(&self, type_writer:&dyn TypeWriter) -> String {
+        let mut s = format!("CREATE OR REPLACE {} {}({})", self.kind(), type_writer.schema(&self.path), self.args_sql(true));
+        if let Some(returns) = self.returns.as_ref().filter(|_| ! self.is_procedure()) {
+            s += format!(" RETURNS {returns}").as_str()
+        }
+        s += format!("\nLANGUAGE {}", self.language).as_str();
+        if let Some(vol) = self.volatility.as_ref().filter(|_| ! self.is_procedure()) {
+            s += format!(" {vol}").as_str()
+        }
+        if self.security_definer {
+            s += " SECURITY DEFINER"
+        }
+        let quote = if self.body.contains("$$") { "$body$" } else { "$$" };
+        s + format!("\nAS {quote}\n{}\n{quote};", self.body.trim()).as_str()
+    }
+    fn delimited_sql(&self, type_writer:&dyn TypeWriter, delimiter:&str) -> String {
+        let name = type_writer.schema(&self.path);
+        let mut s = format!("DELIMITER {delimiter}\nDROP {} IF EXISTS {name}{delimiter}\nCREATE {} {name}({})",
+            self.kind(), self.kind(), self.args_sql(false));
+        if let Some(returns) = self.returns.as_ref().filter(|_| ! self.is_procedure()) {
+            s += format!(" RETURNS {returns}").as_str()
+        }
+        if let Some(vol) = &self.volatility {
+            s += match vol {
+                Volatility::Immutable=>"\nDETERMINISTIC",
+                Volatility::Stable=>"\nNOT DETERMINISTIC READS SQL DATA",
+                Volatility::Volatile=>"\nNOT DETERMINISTIC",
+            }
+        }
+        s += if self.security_definer { "\nSQL SECURITY DEFINER" } else { "\nSQL SECURITY INVOKER" };
+        s + format!("\n{}{delimiter}\nDELIMITER ;", self.body.trim()).as_str()
+    }
+}
+#[typetag::serde]
+impl DBObject for Function {
+    fn to_sql(&self, type_writer:&dyn TypeWriter) -> String {
+        if ! type_writer.supports_functions() {
+            return "".to_owned()
+        }
+        match type_writer.routine_delimiter() {
+            Some(delimiter) => self.delimited_sql(type_writer, delimiter),
+            None => self.dollar_quoted_sql(type_writer),
+        }
+    }
+    fn drop_sql(&self, type_writer:&dyn TypeWriter) -> String {
+        if type_writer.supports_functions() {
+            format!("DROP {} {};", self.kind(), type_writer.schema(&self.path))
+        } else {
+            "".to_owned()
+        }
+    }
+    fn is_top_level(&self) -> bool { true }
+    fn path(&self) -> Option<&ObjectPath> { Some(&self.path) }
+    fn depends_on(&self) -> Vec<ObjectPath> { self.depends_on.to_owned() }
+    fn top_level_to_sql(&self, type_writer:&dyn TypeWriter, _delayed: &Vec<&Box<& dyn DBObject>>) -> String {
+        self.to_sql(type_writer)
+    }
+}
+
+/// Timing of a Trigger
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub enum TriggerTiming {
+    Before,
+    After,
+    InsteadOf,
+}
+impl fmt::Display for TriggerTiming {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            TriggerTiming::Before=>"BEFORE",
+            TriggerTiming::After=>"AFTER",
+            TriggerTiming::InsteadOf=>"INSTEAD OF",
+        })
+    }
+}
+
+/// Event firing a Trigger
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub enum TriggerEvent {
+    Insert,
+    Update,
+    /// UPDATE of some fields only
+    UpdateOf(FieldNames),
+    Delete,
+    Truncate,
+}
+impl TriggerEvent {
+    fn keyword(&self) -> &str {
+        match self {
+            TriggerEvent::Insert=>"INSERT",
+            TriggerEvent::Update | TriggerEvent::UpdateOf(_)=>"UPDATE",
+            TriggerEvent::Delete=>"DELETE",
+            TriggerEvent::Truncate=>"TRUNCATE",
+        }
+    }
+}
+impl fmt::Display for TriggerEvent {
+    fn fmt(&self, f:&mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TriggerEvent::UpdateOf(fields)=>write!(f, "UPDATE OF {}", fields.join(",")),
+            _=>f.write_str(self.keyword()),
+        }
+    }
+}
+
+/// Granularity of a Trigger
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub enum TriggerScope {
+    #[default]
+    Row,
+    Statement,
+}
+
+/// TRIGGER generator
+///
+/// TypeWriters using trigger functions (Postgresql) need function,
+/// the other ones need body: the statements run by the trigger, without BEGIN/END.
+/// Triggers on many events are split in one trigger per event (named name_event) where needed
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Trigger {
+    pub name: String,
+    #[serde(default)]
+    pub table: ObjectPath,
+    pub timing: TriggerTiming,
+    pub events: Vec<TriggerEvent>,
+    #[serde(default)]
+    pub for_each: TriggerScope,
+    /// Optional condition (using NEW/OLD)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<String>,
+    /// Function executed by the trigger (Postgresql)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function: Option<ObjectPath>,
+    /// Inline body (Mysql, Sqlite)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+}
+impl Trigger {
+    /// Create a FOR EACH ROW Trigger on table
+    pub fn new(name:&str, table:&ObjectPath, timing:TriggerTiming, events:Vec<TriggerEvent>) -> Self {
+        Trigger {
+            name: name.to_string(),
+            table: table.to_owned(),
+            timing,
+            events,
+            for_each: TriggerScope::Row,
+            when: None,
+            function: None,
+            body: None,
+        }
+    }
+    /// Check that the Trigger can be written: it needs a function or a body
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if self.function.is_none() && self.body.is_none() {
+            return Err(format!("trigger {} on {} has neither a function nor a body", self.name, self.table.full_name()).into())
+        }
+        Ok(())
+    }
+    /// Get the warnings about the TypeWriters skipping this Trigger, as it lacks the function or body they need
+    pub fn warnings(&self) -> Vec<String> {
+        let mut rv = Vec::new();
+        if self.function.is_none() && self.body.is_some() {
+            rv.push(format!("trigger {} on {} has no function, skipped by Postgresql", self.name, self.table.full_name()))
+        }
+        if self.body.is_none() && self.function.is_some() {
+            rv.push(format!("trigger {} on {} has no body, skipped by Mysql and Sqlite", self.name, self.table.full_name()))
+        }
+        rv
+    }
+    fn function_sql(&self, type_writer:&dyn TypeWriter, function:&ObjectPath) -> String {
+        let events:Vec<String> = self.events.iter().map(|e| e.to_string()).collect();
+        let mut s = format!("CREATE TRIGGER {} {} {} ON {}\nFOR EACH {}",
+            self.name, self.timing, events.join(" OR "), type_writer.schema(&self.table),
+            if self.for_each == TriggerScope::Row { "ROW" } else { "STATEMENT" });
+        if let Some(when) = &self.when {
+            s += format!("\nWHEN ({when})").as_str()
+        }
+        s + format!("\nEXECUTE FUNCTION {}();", type_writer.schema(function)).as_str()
+    }
+    /// Names of the inline body triggers created for every event
+    fn event_triggers(&self, type_writer:&dyn TypeWriter) -> Vec<(String, &TriggerEvent)> {
+        let split = self.events.len() > 1 && ! type_writer.supports_multi_event_triggers();
+        self.events.iter()
+            .filter(|e| **e != TriggerEvent::Truncate || type_writer.supports_statement_triggers())
+            .map(|event| {
+                let name = if split {
+                    format!("{}_{}", self.name, event.keyword().to_lowercase())
+                } else {
+                    self.name.to_owned()
+                };
+                (type_writer.schema(&ObjectPath { schema: self.table.schema.to_owned(), name, otype: ObjectType::Table }), event)
+            })
+            .collect()
+    }
+    fn body_sql(&self, type_writer:&dyn TypeWriter, body:&str) -> Vec<String> {
+        let mut rv = Vec::new();
+        for (name, event) in self.event_triggers(type_writer) {
+            let mut when = self.when.to_owned();
+            let on = match event {
+                TriggerEvent::UpdateOf(fields) if ! type_writer.supports_trigger_update_of() => {
+                    // no UPDATE OF: fire when any of the fields changed
+                    let same:Vec<String> = fields.iter().map(|f| format!("NEW.{f} <=> OLD.{f}")).collect();
+                    let changed = format!("NOT ({})", same.join(" AND "));
+                    when = Some(match when {
+                        Some(w) => format!("({w}) AND {changed}"),
+                        None => changed,
+                    });
+                    event.keyword().to_owned()
+                },
+                _=>event.to_string(),
+            };
+            let mut s = format!("CREATE TRIGGER {} {} {} ON {}\nFOR EACH ROW", name, self.timing, on, type_writer.schema(&self.table));
+            let mut body = body.trim().to_owned();
+            if ! body.ends_with(';') {
+                body += ";"
+            }
+            if let Some(cond) = &when {
+                if type_writer.supports_trigger_when() {
+                    s += format!("\nWHEN {cond}").as_str()
+                } else {
+                    body = format!("IF {cond} THEN\n{body}\nEND IF;")
+                }
+            }
+            s += format!("\nBEGIN\n{body}\nEND").as_str();
+            rv.push(s)
+        }
+        rv
+    }
+}
+#[typetag::serde]
+impl DBObject for Trigger {
+    fn to_sql(&self, type_writer:&dyn TypeWriter) -> String {
+        if (self.for_each == TriggerScope::Statement && ! type_writer.supports_statement_triggers())
+            || (self.timing == TriggerTiming::InsteadOf && ! type_writer.supports_instead_of_triggers()) {
+            return "".to_owned()
+        }
+        if type_writer.trigger_uses_function() {
+            return match &self.function {
+                Some(function) => self.function_sql(type_writer, function),
+                None => "".to_owned(),
+            }
+        }
+        let body = match &self.body {
+            Some(body) => body,
+            None => return "".to_owned(),
+        };
+        let triggers = self.body_sql(type_writer, body);
+        if triggers.is_empty() {
+            return "".to_owned()
+        }
+        match type_writer.routine_delimiter() {
+            Some(delimiter) => format!("DELIMITER {delimiter}\n{}{delimiter}\nDELIMITER ;", triggers.join(format!("{delimiter}\n").as_str())),
+            None => format!("{};", triggers.join(";\n")),
+        }
+    }
+    fn drop_sql(&self, type_writer:&dyn TypeWriter) -> String {
+        if self.to_sql(type_writer).is_empty() {
+            return "".to_owned()
+        }
+        if type_writer.trigger_uses_function() {
+            return format!("DROP TRIGGER {} ON {};", self.name, type_writer.schema(&self.table))
+        }
+        let drops:Vec<String> = self.event_triggers(type_writer).iter()
+            .map(|(name, _)| format!("DROP TRIGGER {name};"))
+            .collect();
+        drops.join("\n")
+    }
+    fn is_top_level(&self) -> bool { true }
+    fn depends_on(&self) -> Vec<ObjectPath> {
+        let mut deps = vec![self.table.to_owned()];
+        if let Some(function) = &self.function {
+            deps.push(function.to_owned())
+        }
+        deps
+    }
+    fn top_level_to_sql(&self, type_writer:&dyn TypeWriter, _delayed: &Vec<&Box<& dyn DBObject>>) -> String {
+        self.to_sql(type_writer)
+    }
 }
 
 /// List of Field definitions (for De/Serialization)
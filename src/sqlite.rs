@@ -0,0 +1,347 @@
+use crate::diff::{REBUILD_BEGIN, REBUILD_END};
+use crate::sql::*;
+use crate::type_writers::Sqlite;
+use crate::Processor;
+use crate::migrations::*;
+use rusqlite::{Connection, OpenFlags};
+use std::error::Error;
+
+type Objects = Vec<Box<dyn DBObject>>;
+
+/// Column of a table, from pragma table_info
+struct ColumnInfo {
+    name: String,
+    dtype: String,
+    not_null: bool,
+    default: Option<String>,
+    pk: i64,
+}
+
+/// Index of a table, from pragma index_list
+struct IndexInfo {
+    name: String,
+    unique: bool,
+    /// "c" (CREATE INDEX), "u" (UNIQUE constraint) or "pk"
+    origin: String,
+    partial: bool,
+}
+
+/// Reference of a foreign key, from pragma foreign_key_list
+struct ReferenceInfo {
+    id: i64,
+    table: String,
+    from: String,
+    to: Option<String>,
+    on_update: String,
+    on_delete: String,
+}
+
+fn fk_on(action:&str) -> Result<FKOn, Box<dyn Error>> {
+    Ok(match action.to_uppercase().as_str() {
+        "CASCADE" => FKOn::Cascade,
+        "RESTRICT" => FKOn::Restrict,
+        "SET NULL" => FKOn::SetNull,
+        "SET DEFAULT" => FKOn::SetDefault,
+        "NO ACTION" => FKOn::NoAction,
+        other => return Err(format!("unknown referential action '{other}'").into()),
+    })
+}
+
+/// Default value as stored by SQLite (an SQL expression), without the quotes of literals
+fn default_value(default:&str) -> String {
+    for quote in ['\'', '"'] {
+        if default.len() > 1 && default.starts_with(quote) && default.ends_with(quote) {
+            let q = quote.to_string();
+            return default[1..default.len() - 1].replace(&q.repeat(2), &q)
+        }
+    }
+    default.to_owned()
+}
+
+fn columns(conn:&Connection, table:&str) -> Result<Vec<ColumnInfo>, Box<dyn Error>> {
+    let mut stmt = conn.prepare("SELECT name, type, \"notnull\", dflt_value, pk FROM pragma_table_info(?1) ORDER BY cid")?;
+    let rows = stmt.query_map([table], |r| Ok(ColumnInfo {
+        name: r.get(0)?,
+        dtype: r.get(1)?,
+        not_null: r.get(2)?,
+        default: r.get(3)?,
+        pk: r.get(4)?,
+    }))?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+fn index_list(conn:&Connection, table:&str) -> Result<Vec<IndexInfo>, Box<dyn Error>> {
+    let mut stmt = conn.prepare("SELECT name, \"unique\", origin, partial FROM pragma_index_list(?1) ORDER BY seq DESC")?;
+    let rows = stmt.query_map([table], |r| Ok(IndexInfo {
+        name: r.get(0)?,
+        unique: r.get(1)?,
+        origin: r.get(2)?,
+        partial: r.get(3)?,
+    }))?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+/// Names of the columns of index, None for expressions
+fn index_columns(conn:&Connection, index:&str) -> Result<Vec<Option<String>>, Box<dyn Error>> {
+    let mut stmt = conn.prepare("SELECT name FROM pragma_index_info(?1) ORDER BY seqno")?;
+    let rows = stmt.query_map([index], |r| r.get(0))?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+fn references(conn:&Connection, table:&str) -> Result<Vec<ReferenceInfo>, Box<dyn Error>> {
+    let mut stmt = conn.prepare("SELECT id, \"table\", \"from\", \"to\", on_update, on_delete FROM pragma_foreign_key_list(?1) ORDER BY id DESC, seq")?;
+    let rows = stmt.query_map([table], |r| Ok(ReferenceInfo {
+        id: r.get(0)?,
+        table: r.get(1)?,
+        from: r.get(2)?,
+        to: r.get(3)?,
+        on_update: r.get(4)?,
+        on_delete: r.get(5)?,
+    }))?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+fn read_table(conn:&Connection, name:&str, sql:&str, objs:&mut Objects, warnings:&mut Vec<String>) -> Result<(), Box<dyn Error>> {
+    let path = ObjectPath::new_table_from_full_name(name);
+    let cols = columns(conn, name)?;
+    let autoinc = sql.to_uppercase().contains("AUTOINCREMENT");
+    let mut fields = Fields::new();
+    for col in cols.iter() {
+        let dtype = match FieldType::from_sql_type(&col.dtype) {
+            Some(dtype) => dtype,
+            None => {
+                warnings.push(format!("type '{}' of {name}.{} read as Txt", col.dtype, col.name));
+                FieldType::Txt
+            },
+        };
+        let mut attrs = FieldAttributes::new(dtype);
+        attrs.empty = ! col.not_null && col.pk == 0;
+        attrs.primary_key = col.pk > 0;
+        attrs.defval = col.default.as_deref().map(default_value);
+        if autoinc && col.pk > 0 && attrs.dtype == FieldType::Int {
+            attrs.dtype = FieldType::AutoInc
+        }
+        fields.push(Field::new(&col.name, &attrs))
+    }
+    let mut indexes = Vec::new();
+    let mut unique_groups = 0;
+    for info in index_list(conn, name)? {
+        if info.origin == "pk" {
+            continue
+        }
+        let names = index_columns(conn, &info.name)?;
+        if names.iter().any(|n| n.is_none()) || info.partial {
+            warnings.push(format!("index {} on expressions or partial, skipped", info.name));
+            continue
+        }
+        let names:FieldNames = names.into_iter().flatten().collect();
+        if info.origin == "u" {
+            for f in fields.iter_mut().filter(|f| names.contains(&f.name)) {
+                f.attributes.unique = true
+            }
+            unique_groups += 1
+        } else {
+            let idx = Index::new(&path, &names);
+            if info.unique {
+                warnings.push(format!("unique index {} read as the non unique index {}", info.name, idx.name()))
+            }
+            indexes.push(idx)
+        }
+    }
+    if unique_groups > 1 {
+        warnings.push(format!("UNIQUE constraints of {name} merged into one"))
+    }
+    let mut fks = ForeignKeys::new();
+    let refs = references(conn, name)?;
+    let mut ids:Vec<i64> = refs.iter().map(|r| r.id).collect();
+    ids.dedup();
+    for id in ids {
+        let parts:Vec<&ReferenceInfo> = refs.iter().filter(|r| r.id == id).collect();
+        let ref_fields:Option<FieldNames> = parts.iter().map(|r| r.to.to_owned()).collect();
+        let ref_fields = match ref_fields {
+            Some(ref_fields) => ref_fields,
+            None => {
+                warnings.push(format!("reference from {name} to the primary key of {} skipped", parts[0].table));
+                continue
+            },
+        };
+        let from:FieldNames = parts.iter().map(|r| r.from.to_owned()).collect();
+        let mut fk = ForeignKey::new(&path, &from, &ObjectPath::new_table_from_full_name(&parts[0].table), &ref_fields);
+        fk.on_delete = fk_on(&parts[0].on_delete)?;
+        fk.on_update = fk_on(&parts[0].on_update)?;
+        if fk.sets_null() && fields.iter().any(|f| from.contains(&f.name) && ! f.attributes.empty) {
+            warnings.push(format!("{} uses SET NULL on NOT NULL fields, skipped", fk.name()));
+            continue
+        }
+        fks.push(fk)
+    }
+    let table = Table::new(&path, fields, if fks.is_empty() { None } else { Some(fks) });
+    objs.push(Box::new(table));
+    for index in indexes {
+        objs.push(Box::new(index))
+    }
+    Ok(())
+}
+
+/// Read the Tables (with their ForeignKeys) and Index'es of a SQLite database,
+/// returning them with the warnings about what could not be read
+pub fn read_sqlite(conn:&Connection) -> Result<(Objects, Vec<String>), Box<dyn Error>> {
+    let mut objs = Objects::new();
+    let mut warnings = Vec::new();
+    // the migrations bookkeeping is not part of the schema
+    let mut stmt = conn.prepare("SELECT type, name, sql FROM sqlite_master WHERE name NOT LIKE 'sqlite_%' AND tbl_name <> ?1 ORDER BY rowid")?;
+    let rows = stmt.query_map([MIGRATIONS_TABLE], |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?, r.get::<_, Option<String>>(2)?)))?;
+    for row in rows {
+        let (otype, name, sql) = row?;
+        match otype.as_str() {
+            "table" => read_table(conn, &name, sql.as_deref().unwrap_or(""), &mut objs, &mut warnings)?,
+            // read along with their tables
+            "index" => (),
+            _ => warnings.push(format!("{otype} {name} not read")),
+        }
+    }
+    Ok((objs, warnings))
+}
+
+/// Read the Tables and Index'es of a SQLite database file (opened read only)
+pub fn read_sqlite_file(file_name:&str) -> Result<(Objects, Vec<String>), Box<dyn Error>> {
+    let conn = Connection::open_with_flags(file_name, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    read_sqlite(&conn)
+}
+
+/// Generated SQL statement rejected by SQLite
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailedStatement {
+    /// Kind and path of the object generating the statement, like "Table public.users"
+    pub object: String,
+    pub sql: String,
+    /// Error reported by SQLite
+    pub error: String,
+}
+
+impl std::fmt::Display for FailedStatement {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}: {}", self.object, self.error)
+    }
+}
+
+/// Describe obj by its kind and path
+fn describe(obj:&dyn DBObject) -> String {
+    match obj.path() {
+        Some(path) => format!("{} {}", obj.typetag_name(), path.full_name()),
+        None => obj.typetag_name().to_owned(),
+    }
+}
+
+/// Run the SQL generated for the objects of processor, written with the Sqlite
+/// TypeWriter, on an in-memory SQLite database, returning the statements that failed
+pub fn verify_sql(processor:&Processor) -> Result<Vec<FailedStatement>, Box<dyn Error>> {
+    let mut sqlite = Processor::new(Some(Box::new(Sqlite{})));
+    for obj in processor.objects().iter() {
+        sqlite.add(**obj);
+    }
+    let conn = Connection::open_in_memory()?;
+    let mut failed = Vec::new();
+    for (obj, sql) in sqlite.sql_statements_by_object() {
+        if let Err(e) = conn.execute_batch(&sql) {
+            failed.push(FailedStatement {
+                object: describe(obj),
+                sql,
+                error: e.to_string(),
+            })
+        }
+    }
+    Ok(failed)
+}
+
+/// MigrationDriver for a SQLite database
+pub struct SqliteDriver {
+    conn: Connection,
+}
+
+impl SqliteDriver {
+    pub fn new(conn:Connection) -> Self {
+        SqliteDriver { conn }
+    }
+    /// Open (or create) a SQLite database file
+    pub fn open(file_name:&str) -> Result<Self, Box<dyn Error>> {
+        Ok(SqliteDriver::new(Connection::open(file_name)?))
+    }
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+    /// Run sql and record in a transaction. Table rebuilds (between REBUILD_BEGIN and
+    /// REBUILD_END) are run in it with foreign keys off, checking the rebuilt Tables
+    /// (if foreign keys were on) before committing
+    fn run(&mut self, sql:&str, record:&str) -> Result<(), Box<dyn Error>> {
+        let rebuilt = rebuilt_tables(sql);
+        if rebuilt.is_empty() {
+            return self.transaction(sql, record, &[])
+        }
+        let sql = sql.replace(REBUILD_BEGIN, "").replace(REBUILD_END, "");
+        let foreign_keys:bool = self.conn.query_row("PRAGMA foreign_keys", [], |r| r.get(0))?;
+        // foreign_keys can not be changed inside of a transaction
+        self.conn.execute_batch("PRAGMA foreign_keys=off;")?;
+        let rv = self.transaction(&sql, record, if foreign_keys { &rebuilt } else { &[] });
+        if foreign_keys {
+            self.conn.execute_batch("PRAGMA foreign_keys=on;")?
+        }
+        rv
+    }
+    fn transaction(&mut self, sql:&str, record:&str, checked:&[String]) -> Result<(), Box<dyn Error>> {
+        let tx = self.conn.transaction()?;
+        tx.execute_batch(sql)?;
+        tx.execute_batch(record)?;
+        let mut violations = Vec::new();
+        for table in checked.iter() {
+            let mut stmt = tx.prepare("SELECT rowid, parent FROM pragma_foreign_key_check(?1)")?;
+            let rows = stmt.query_map([table], |r| {
+                let rowid:Option<i64> = r.get(0)?;
+                let parent:String = r.get(1)?;
+                Ok(format!("{table} row {} references a missing {parent} row", rowid.map_or("?".to_owned(), |id| id.to_string())))
+            })?;
+            for row in rows {
+                violations.push(row?)
+            }
+        }
+        if ! violations.is_empty() {
+            return Err(format!("foreign keys would be violated, rolled back: {}", violations.join(", ")).into())
+        }
+        Ok(tx.commit()?)
+    }
+}
+
+/// Tables rebuilt by sql, from their ALTER TABLE new_<table> RENAME TO <table>
+fn rebuilt_tables(sql:&str) -> Vec<String> {
+    if ! sql.contains(REBUILD_BEGIN) {
+        return Vec::new()
+    }
+    sql.lines().filter_map(|line| {
+        let (temp, table) = line.strip_prefix("ALTER TABLE ")?.strip_suffix(';')?.split_once(" RENAME TO ")?;
+        let temp = temp.rsplit('.').next()?;
+        (temp.strip_prefix("new_")? == table).then(|| table.to_owned())
+    }).collect()
+}
+
+impl MigrationDriver for SqliteDriver {
+    fn init(&mut self) -> Result<(), Box<dyn Error>> {
+        Ok(self.conn.execute_batch(&create_table_sql())?)
+    }
+    fn applied(&mut self) -> Result<Vec<AppliedMigration>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(&select_sql())?;
+        let rows = stmt.query_map([], |r| Ok(AppliedMigration {
+            version: r.get(0)?,
+            name: r.get(1)?,
+            checksum: r.get(2)?,
+            applied_at: r.get(3)?,
+        }))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+    fn apply(&mut self, migration:&Migration) -> Result<(), Box<dyn Error>> {
+        self.run(&migration.up, &record_sql(migration))
+    }
+    fn revert(&mut self, migration:&Migration) -> Result<(), Box<dyn Error>> {
+        let down = migration.down.as_deref().unwrap_or("");
+        self.run(down, &unrecord_sql(migration.version))
+    }
+}
@@ -1,21 +1,112 @@
-use crate::sql::{DBObject, TypeWriter};
+use crate::sql::{DBObject, Mixin, Table, Trigger, TypeWriter};
 use crate::type_writers::Postgresql;
 use crate::util::*;
+use crate::dbml::{read_dbml, write_dbml};
+use crate::ddl::read_sql;
+use std::collections::{BTreeSet, HashMap};
 use std::error::Error;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::prelude::*;
 
 type BxTypeWriter = Box<dyn TypeWriter>;
 type BxObject<'a> = &'a Box<&'a dyn DBObject>;
 type Objects = Vec<Box<dyn DBObject>>;
+type Group<'a> = (BxObject<'a>, Vec<BxObject<'a>>);
+
+/// Stable topological sort of groups so that every object comes after the ones it depends on
+/// (dependency cycles are left in the original order)
+fn sort_by_dependencies(groups:Vec<Group>) -> Vec<Group> {
+    let mut providers:HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, (obj, _)) in groups.iter().enumerate() {
+        if let Some(path) = obj.path() {
+            providers.entry(path.full_name()).or_default().push(i)
+        }
+    }
+    let mut dependents = vec![Vec::new(); groups.len()];
+    let mut blockers = vec![0usize; groups.len()];
+    for (i, (obj, _)) in groups.iter().enumerate() {
+        for dep in obj.depends_on() {
+            for &j in providers.get(&dep.full_name()).into_iter().flatten() {
+                if i != j {
+                    dependents[j].push(i);
+                    blockers[i] += 1
+                }
+            }
+        }
+    }
+    let mut ready:BTreeSet<usize> = (0..groups.len()).filter(|&i| blockers[i] == 0).collect();
+    let mut pending:BTreeSet<usize> = (0..groups.len()).collect();
+    let mut order = Vec::with_capacity(groups.len());
+    while let Some(&first) = pending.iter().next() {
+        // nothing is ready on a dependency cycle, so the first pending group goes next
+        let i = ready.iter().next().copied().unwrap_or(first);
+        ready.remove(&i);
+        pending.remove(&i);
+        order.push(i);
+        for &k in dependents[i].iter() {
+            blockers[k] -= 1;
+            if blockers[k] == 0 && pending.contains(&k) {
+                ready.insert(k);
+            }
+        }
+    }
+    let mut slots:Vec<Option<Group>> = groups.into_iter().map(Some).collect();
+    order.into_iter().filter_map(|i| slots[i].take()).collect()
+}
+
+/// Apply the Mixin's listed by every Table, adding their Index'es and Trigger's after it
+fn resolve_mixins(objs:Objects) -> Result<Objects, Box<dyn Error>> {
+    let mixins:Vec<Mixin> = objs.iter().filter_map(|o| o.downcast_ref::<Mixin>()).cloned().collect();
+    let mut out = Objects::new();
+    for mut obj in objs.into_iter() {
+        let mut extra = Vec::new();
+        if let Some(table) = obj.downcast_mut::<Table>() {
+            for name in std::mem::take(&mut table.mixins) {
+                let mixin = mixins.iter().find(|m| m.name == name)
+                    .ok_or_else(|| format!("{} uses unknown mixin '{name}'", table.path.full_name()))?;
+                extra.append(&mut mixin.apply(table));
+            }
+        }
+        out.push(obj);
+        out.append(&mut extra);
+    }
+    Ok(out)
+}
+
+/// Check the loaded objects, failing on the first invalid one and adding to warnings the ones some writers skip or reject
+fn validate(objs:Objects, warnings:&mut Vec<String>) -> Result<Objects, Box<dyn Error>> {
+    for obj in objs.iter() {
+        if let Some(table) = obj.downcast_ref::<Table>() {
+            table.validate()?;
+            warnings.append(&mut table.warnings())
+        }
+        if let Some(trigger) = obj.downcast_ref::<Trigger>() {
+            trigger.validate()?;
+            warnings.append(&mut trigger.warnings())
+        }
+    }
+    Ok(objs)
+}
+
+/// Options of the SQL written by a Processor
+#[derive(Debug, Clone, Default)]
+pub struct ProcessorOptions {
+    /// Wrap the statements in BEGIN/COMMIT, if the TypeWriter supports transactional DDL
+    pub transaction: bool,
+    /// Create Tables, Index'es and Schemas IF NOT EXISTS (where the TypeWriter supports it),
+    /// so the statements can be run again
+    pub idempotent: bool,
+}
 
 /// DML processor and SQL generator
 ///
 /// Collects DBObject's and creates SQL sql_statements using the supplied
 ///  TypeWriter or Postgresql if none is provided
+#[allow(clippy::vec_box)]
 pub struct Processor<'a> {
     objs: Vec<Box<&'a dyn DBObject>>,
     type_writer:BxTypeWriter,
+    options: ProcessorOptions,
 }
 
 impl <'a> Processor<'a> {
@@ -29,6 +120,7 @@ impl <'a> Processor<'a> {
         Processor {
             objs: Vec::new(),
             type_writer,
+            options: ProcessorOptions::default(),
         }
     }
     /// Create a new Processor optionally specifying a TypeWriter to use
@@ -39,60 +131,98 @@ impl <'a> Processor<'a> {
         }
         me
     }
+    /// Set the options of the SQL written
+    pub fn set_options(&mut self, options:ProcessorOptions) -> &Self {
+        self.options = options;
+        self
+    }
+    /// Get the options of the SQL written
+    pub fn options(&self) -> &ProcessorOptions {
+        &self.options
+    }
     /// Add a DB object
     pub fn add(&mut self, object:&'a dyn DBObject) -> &Self {
         self.objs.push(Box::new(object));
         self
     }
-    fn add_to_toplevel(&self, obj:&mut BxObject, out:&mut Vec<String>, delayed:&mut Vec<&Box<&dyn DBObject>>) {
-        let sql;
-        if delayed.is_empty() {
-            // println!("{:?}: SIMPLE to_sql()", obj);
-            sql = obj.to_sql(self.type_writer.as_ref())
-        } else {
-            // println!("{:?}: TOP-LEVEL to_sql()", obj);
-            sql = obj.top_level_to_sql(self.type_writer.as_ref(), &delayed);
-            delayed.clear()
-        };
-        if ! sql.is_empty() {
-            out.push(sql);
-        }
-    }
-    /// Get the list of serialized SQL sql_statements
-    pub fn sql_statements(&self) -> Vec<String> {
-        let mut out = Vec::new();
+    /// Group every top level object with the (non top level) objects following it
+    fn groups(&self) -> Vec<Group<'_>> {
+        let mut groups: Vec<Group> = Vec::new();
         let mut delayed = Vec::new();
-        let mut top : Option<BxObject> = None;
         for obj in &self.objs {
             if ! obj.is_top_level() {
                 delayed.push(obj)
             } else {
-                if let Some(mut tl) = top {
-                    self.add_to_toplevel(&mut tl, &mut out, &mut delayed);
+                if let Some(last) = groups.last_mut() {
+                    last.1.append(&mut delayed)
                 }
-                top = Some(obj)
+                groups.push((obj, Vec::new()))
             }
         }
-        if let Some(mut tl) = top {
-            self.add_to_toplevel(&mut tl, &mut out, &mut delayed)
+        if let Some(last) = groups.last_mut() {
+            last.1.append(&mut delayed)
+        }
+        sort_by_dependencies(groups)
+    }
+    /// Get the list of serialized SQL sql_statements, along with the top level object generating each
+    pub fn sql_statements_by_object(&self) -> Vec<(&dyn DBObject, String)> {
+        let mut out = Vec::new();
+        let groups = self.groups();
+        for (obj, delayed) in groups.iter() {
+            let sql = if self.options.idempotent {
+                obj.if_not_exists_sql(self.type_writer.as_ref(), delayed)
+            } else if delayed.is_empty() {
+                obj.to_sql(self.type_writer.as_ref())
+            } else {
+                obj.top_level_to_sql(self.type_writer.as_ref(), delayed)
+            };
+            if ! sql.is_empty() {
+                out.push((***obj, sql));
+            }
+        }
+        for (obj, _) in groups.iter() {
+            let sql = obj.post_sql(self.type_writer.as_ref());
+            if ! sql.is_empty() {
+                out.push((***obj, sql));
+            }
         }
         out
     }
+    /// Get the list of serialized SQL sql_statements
+    pub fn sql_statements(&self) -> Vec<String> {
+        let mut statements:Vec<String> = self.sql_statements_by_object().into_iter().map(|(_, sql)| sql).collect();
+        if self.options.transaction && self.type_writer.supports_transactional_ddl() {
+            statements.insert(0, "BEGIN;".to_owned());
+            statements.push("COMMIT;".to_owned());
+        }
+        statements
+    }
     /// Get a String with all of the SQL statments
     pub fn join_sql_statements(&self) -> String {
         self.sql_statements().join("\n")
     }
     /// Write objects to a YAML file
     pub fn serialize_to_yaml_file(&self, file_name:&str) -> Result<(), Box<dyn Error>> {
-        Ok(write_yaml_to_file(file_name, &self.objs)?)
+        write_yaml_to_file(file_name, &self.objs)
     }
     /// Write generated SQL to file
     pub fn write_to_sql_file(&self, file_name:&str) -> Result<(), Box<dyn Error>> {
         let sqls = self.join_sql_statements();
         let mut fh = File::create(file_name)?;
-        Ok(fh.write_all(&sqls.as_bytes())?)
+        Ok(fh.write_all(sqls.as_bytes())?)
+    }
+    /// Write objects to a DBML file
+    pub fn write_to_dbml_file(&self, file_name:&str) -> Result<(), Box<dyn Error>> {
+        let objs:Vec<&dyn DBObject> = self.objs.iter().map(|o| **o).collect();
+        let mut fh = File::create(file_name)?;
+        Ok(fh.write_all(write_dbml(&objs).as_bytes())?)
+    }
+    /// Get the TypeWriter used to generate SQL
+    pub fn type_writer(&self) -> &dyn TypeWriter {
+        self.type_writer.as_ref()
     }
     /// Get number of objects present
+    #[allow(clippy::vec_box)]
     pub fn objects(&self) -> &Vec<Box<&'a dyn DBObject>> {
         &self.objs
     }
@@ -102,25 +232,54 @@ impl <'a> Processor<'a> {
 /// DML yaml loader
 ///
 /// Loads DBObject's from a .yaml file and permits accesing them
-/// Used for giving scope to Processor
+/// Used for giving scope to Processor. Mixin's used by Tables are applied when loading
 pub struct Loader {
     objs: Objects,
+    warnings: Vec<String>,
 }
 
 impl Loader {
     /// Create Loader from YAML in a String
     pub fn new(data:&str) -> Result<Self, Box<dyn Error>> {
-        Ok(Loader {
-            objs: read_yaml_from_string(data).expect(format!("To load objects from string '{data}'").as_str())
-        })
+        let mut warnings = Vec::new();
+        let objs = validate(resolve_mixins(read_yaml_from_string(data)?)?, &mut warnings)?;
+        Ok(Loader { objs, warnings })
     }
     /// Create Loader reading from a YAML file
     pub fn new_from_file(file_name:&str) -> Result<Self, Box<dyn Error>> {
-        Ok(Loader {
-            objs: read_yaml_from_file(file_name).expect(format!("To load objects from '{file_name}'").as_str())
-        })
+        let mut warnings = Vec::new();
+        let objs = validate(resolve_mixins(read_yaml_from_file(file_name)?)?, &mut warnings)?;
+        Ok(Loader { objs, warnings })
+    }
+    /// Create Loader from SQL DDL in a String
+    pub fn new_from_sql(data:&str) -> Result<Self, Box<dyn Error>> {
+        let (objs, mut warnings) = read_sql(data)?;
+        Ok(Loader { objs: validate(objs, &mut warnings)?, warnings })
+    }
+    /// Create Loader reading from a SQL DDL file
+    pub fn new_from_sql_file(file_name:&str) -> Result<Self, Box<dyn Error>> {
+        Loader::new_from_sql(&fs::read_to_string(file_name)?)
+    }
+    /// Create Loader from DBML in a String
+    pub fn new_from_dbml(data:&str) -> Result<Self, Box<dyn Error>> {
+        let (objs, mut warnings) = read_dbml(data)?;
+        Ok(Loader { objs: validate(objs, &mut warnings)?, warnings })
+    }
+    /// Create Loader reading from a DBML file
+    pub fn new_from_dbml_file(file_name:&str) -> Result<Self, Box<dyn Error>> {
+        Loader::new_from_dbml(&fs::read_to_string(file_name)?)
+    }
+    /// Create Loader introspecting a SQLite database file
+    #[cfg(feature = "sqlite")]
+    pub fn new_from_sqlite_file(file_name:&str) -> Result<Self, Box<dyn Error>> {
+        let (objs, mut warnings) = crate::sqlite::read_sqlite_file(file_name)?;
+        Ok(Loader { objs: validate(objs, &mut warnings)?, warnings })
     }
     pub fn objects(&self) -> &Objects {
         &self.objs
     }
+    /// Get the warnings about the source elements that could not be loaded
+    pub fn warnings(&self) -> &Vec<String> {
+        &self.warnings
+    }
 }
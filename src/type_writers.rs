@@ -1,4 +1,4 @@
-use crate::sql::{TypeWriter, FieldType, ObjectPath};
+use crate::sql::{TypeWriter, FieldType, FKOn, Identity, ObjectPath, CommentStyle, AlterStyle};
 
 /// PostgreSQL type serializator
 #[derive(Debug)]
@@ -16,6 +16,22 @@ impl TypeWriter for Postgresql {
         }
     }
     fn supports_sequences(&self) -> bool { true }
+    fn supports_identity(&self) -> bool { true }
+}
+
+/// PostgreSQL type serializator using IDENTITY columns instead of serial for AutoInc fields
+#[derive(Debug, Default)]
+pub struct PostgresqlIdentity {
+    pub identity: Identity,
+}
+impl TypeWriter for PostgresqlIdentity {
+    fn id(&self) -> &str { "pgsql" }
+    fn field_type(&self, field_type:&FieldType) -> String {
+        Postgresql{}.field_type(field_type)
+    }
+    fn supports_sequences(&self) -> bool { true }
+    fn supports_identity(&self) -> bool { true }
+    fn identity(&self) -> Option<Identity> { Some(self.identity.to_owned()) }
 }
 
 /// MySQL type serializator
@@ -33,6 +49,32 @@ impl TypeWriter for Mysql {
             FieldType::AutoInc => "integer auto_increment".to_owned(),
         }
     }
+    fn supports_fk_action(&self, action:&FKOn) -> bool { *action != FKOn::SetDefault }
+    fn supports_fk_match(&self) -> bool { false }
+    fn supports_deferrable(&self) -> bool { false }
+    fn supports_not_valid(&self) -> bool { false }
+    fn supports_identity(&self) -> bool { true }
+    // there is no IDENTITY, only its width is kept on the auto_increment column
+    fn identity_type(&self, identity:&Identity) -> String {
+        format!("{} auto_increment", self.field_type(&identity.width.field_type()))
+    }
+    fn supports_virtual_columns(&self) -> bool { true }
+    fn supports_materialized_views(&self) -> bool { false }
+    fn routine_delimiter(&self) -> Option<&str> { Some("$$") }
+    fn trigger_uses_function(&self) -> bool { false }
+    fn supports_multi_event_triggers(&self) -> bool { false }
+    fn supports_statement_triggers(&self) -> bool { false }
+    fn supports_trigger_when(&self) -> bool { false }
+    fn supports_trigger_update_of(&self) -> bool { false }
+    fn supports_instead_of_triggers(&self) -> bool { false }
+    fn comment_style(&self) -> CommentStyle { CommentStyle::Inline }
+    fn alter_style(&self) -> AlterStyle { AlterStyle::Modify }
+    fn supports_transactional_ddl(&self) -> bool { false }
+    fn supports_index_if_not_exists(&self) -> bool { false }
+    fn supports_granted_by(&self) -> bool { false }
+    fn all_tables_in_schema(&self, schema:&str) -> String { format!("{schema}.*") }
+    // values are converted on assignment, and CAST only takes MySQL's own type names
+    fn cast(&self, value:&str, _sql_type:&str) -> String { value.to_owned() }
 }
 
 /// SQLite type serializator
@@ -57,4 +99,17 @@ impl TypeWriter for Sqlite {
     fn supports_schemas(&self) -> bool { false }
     fn supports_permissions(&self) -> bool { false }
     fn supports_auto_increment(&self) -> bool { false }
+    fn supports_fk_match(&self) -> bool { false }
+    fn supports_not_valid(&self) -> bool { false }
+    fn supports_virtual_columns(&self) -> bool { true }
+    fn supports_materialized_views(&self) -> bool { false }
+    fn supports_view_check_option(&self) -> bool { false }
+    fn supports_functions(&self) -> bool { false }
+    fn trigger_uses_function(&self) -> bool { false }
+    fn supports_multi_event_triggers(&self) -> bool { false }
+    fn supports_statement_triggers(&self) -> bool { false }
+    fn trigger_sets_new(&self) -> bool { false }
+    fn current_user(&self) -> Option<&str> { None }
+    fn comment_style(&self) -> CommentStyle { CommentStyle::SqlComment }
+    fn alter_style(&self) -> AlterStyle { AlterStyle::Rebuild }
 }
\ No newline at end of file
@@ -23,13 +23,13 @@ pub fn read_yaml_from_file<T:for<'de> Deserialize<'de>, P: AsRef<Path>>(path: P)
 
 /// Read file into a String
 pub fn read_file_into_string(file:&str) -> String {
-    fs::read_to_string(file).expect(format!("To read '{file}' into string").as_str())
+    fs::read_to_string(file).unwrap_or_else(|_| panic!("To read '{file}' into string"))
 }
 
 /// Serialize YAML object and write it to a file
 pub fn write_yaml_to_file<I:for<'de> Serialize>(file_name:&str, doc:&I)  -> Result<(), Box<dyn Error>> {
     let serialized = serde_yaml::to_string(&doc)?;
     let mut fh = File::create(file_name)?;
-    fh.write_all(&serialized.as_bytes())?;
+    fh.write_all(serialized.as_bytes())?;
     Ok(())
 }
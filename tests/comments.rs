@@ -0,0 +1,80 @@
+use std::fs::remove_file;
+
+use dml_tools::Loader;
+use dml_tools::Processor;
+use dml_tools::type_writers::*;
+use dml_tools::sql::*;
+
+const COMMENTS_FILE: &str = "local-comments.yaml";
+
+fn commented_table() -> Table {
+    let mut id = FieldAttributes::new_pk(FieldType::Int);
+    id.comment = Some("User's identifier".to_owned());
+    let fields = vec![
+        Field::new("id", &id),
+        Field::new("email", &FieldAttributes::new(FieldType::Txt)),
+    ];
+    let mut table = Table::new(&ObjectPath::new_table("demo", "users"), fields, None);
+    table.comment = Some("Registered users".to_owned());
+    table
+}
+
+#[test]
+fn test_comments_pgsql() {
+    assert_eq!(commented_table().to_sql(&Postgresql{}), "CREATE TABLE demo.users (
+  id int NOT NULL,
+  email text NULL,
+  CONSTRAINT users_id_pk PRIMARY KEY (id)
+);
+COMMENT ON TABLE demo.users IS 'Registered users';
+COMMENT ON COLUMN demo.users.id IS 'User''s identifier';");
+}
+
+#[test]
+fn test_comments_mysql() {
+    assert_eq!(commented_table().to_sql(&Mysql{}), "CREATE TABLE demo.users (
+  id int NOT NULL COMMENT 'User''s identifier',
+  email text NULL,
+  CONSTRAINT users_id_pk PRIMARY KEY (id)
+) COMMENT='Registered users';");
+}
+
+#[test]
+fn test_comments_sqlite() {
+    assert_eq!(commented_table().to_sql(&Sqlite{}), "-- Registered users
+CREATE TABLE users (
+  -- User's identifier
+  id integer NOT NULL,
+  email text NULL,
+  CONSTRAINT users_id_pk PRIMARY KEY (id)
+);");
+}
+
+#[test]
+fn test_comments_round_trip() {
+    let table = commented_table();
+    let mut proc = Processor::new(None);
+    proc.add(&table);
+    proc.serialize_to_yaml_file(COMMENTS_FILE).expect("to write comments file");
+    let loader = Loader::new_from_file(COMMENTS_FILE).expect("to read comments file");
+    remove_file(COMMENTS_FILE).expect("to remove comments file");
+    let loaded = loader.objects()[0].downcast_ref::<Table>().expect("a table");
+    assert_eq!(loaded.comment, table.comment);
+    assert_eq!(loaded.fields[0].attributes.comment, table.fields[0].attributes.comment);
+}
+
+#[test]
+fn test_comments_description_alias() {
+    let yaml = "
+- tag: Table
+  path: { schema: demo, name: users }
+  description: Registered users
+  fields:
+  - name: id
+    attributes: { description: Identifier }
+";
+    let loader = Loader::new(yaml).expect("to load table");
+    let table = loader.objects()[0].downcast_ref::<Table>().expect("a table");
+    assert_eq!(table.comment.as_deref(), Some("Registered users"));
+    assert_eq!(table.fields[0].attributes.comment.as_deref(), Some("Identifier"));
+}
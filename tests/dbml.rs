@@ -0,0 +1,106 @@
+use dml_tools::dbml::*;
+use dml_tools::sql::*;
+use dml_tools::Loader;
+use dml_tools::Processor;
+
+const DBML: &str = "// schema sales owner rw_user
+
+Table sales.customers {
+  id int [pk, increment]
+  email text [unique, note: 'Contact e-mail']
+  Note: 'Our customers'
+}
+
+Table sales.orders {
+  id int [pk]
+  customer_id int [not null]
+  status text [not null, default: 'new']
+  total double [default: 0]
+  indexes {
+    customer_id
+  }
+}
+
+Ref: sales.orders.customer_id > sales.customers.id [delete: cascade]
+";
+
+#[test]
+fn test_dbml_read() {
+    let loader = Loader::new_from_dbml(DBML).expect("to read DBML");
+    assert!(loader.warnings().is_empty());
+    let objs = loader.objects();
+    assert_eq!(objs.len(), 4);
+    let schema = objs[0].downcast_ref::<Schema>().expect("a schema");
+    assert_eq!(schema.owner, "rw_user");
+    let customers = objs[1].downcast_ref::<Table>().expect("a table");
+    assert_eq!(customers.comment.as_deref(), Some("Our customers"));
+    assert_eq!(customers.fields[0].attributes.dtype, FieldType::AutoInc);
+    assert!(customers.fields[1].attributes.unique);
+    let orders = objs[2].downcast_ref::<Table>().expect("a table");
+    let fks = orders.foreign_keys();
+    assert_eq!(fks.len(), 1);
+    assert_eq!(fks[0].on_delete, FKOn::Cascade);
+    assert_eq!(orders.fields[2].attributes.defval.as_deref(), Some("new"));
+    let index = objs[3].downcast_ref::<Index>().expect("an index");
+    assert_eq!(index.name(), "orders_customer_id_idx");
+}
+
+#[test]
+fn test_dbml_round_trip() {
+    let loader = Loader::new_from_dbml(DBML).expect("to read DBML");
+    let proc = Processor::new_with_objects(loader.objects(), None);
+    let objs:Vec<&dyn DBObject> = loader.objects().iter().map(|o| o.as_ref()).collect();
+    assert_eq!(write_dbml(&objs), DBML);
+    let reloaded = Loader::new_from_dbml(&write_dbml(&objs)).expect("to read written DBML");
+    let reproc = Processor::new_with_objects(reloaded.objects(), None);
+    assert_eq!(proc.join_sql_statements(), reproc.join_sql_statements());
+}
+
+#[test]
+fn test_dbml_aliases_and_warnings() {
+    let dbml = "
+Project shop { database_type: 'PostgreSQL' }
+enum status { new done }
+Table users as U {
+  id integer [primary key]
+  tags jsonb
+}
+Table posts {
+  id int [pk]
+  author_id int [ref: > U.id]
+  \"first editor\" int
+}
+Ref { posts.\"first editor\" - users.id [update: set null] }
+";
+    let loader = Loader::new_from_dbml(dbml).expect("to read DBML");
+    assert_eq!(loader.warnings().len(), 3);
+    let posts = loader.objects()[1].downcast_ref::<Table>().expect("a table");
+    let fks = posts.foreign_keys();
+    assert_eq!(fks.len(), 2);
+    assert_eq!(fks[0].ref_table.name, "users");
+    assert_eq!(fks[1].fields, vec!["first editor".to_owned()]);
+    assert_eq!(fks[1].on_update, FKOn::SetNull);
+}
+
+#[test]
+fn test_dbml_missing_file() {
+    assert!(Loader::new_from_dbml_file("tests/fixtures/missing.dbml").is_err());
+}
+
+#[test]
+fn test_dbml_set_null_on_not_null() {
+    let dbml = "
+Table users {
+  id int [pk]
+}
+Table posts {
+  id int [pk]
+  author_id int [not null, ref: > users.id]
+}
+Ref { posts.author_id > users.id [delete: set null] }
+";
+    let loader = Loader::new_from_dbml(dbml).expect("to read DBML");
+    assert_eq!(loader.warnings(), &vec!["posts_users_author_id_fk uses SET NULL on NOT NULL fields, skipped".to_owned()]);
+    let posts = loader.objects()[1].downcast_ref::<Table>().expect("a table");
+    assert_eq!(posts.foreign_keys().len(), 1);
+}
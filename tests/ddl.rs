@@ -0,0 +1,83 @@
+use dml_tools::sql::*;
+use dml_tools::type_writers::*;
+use dml_tools::util::read_file_into_string;
+use dml_tools::Loader;
+use dml_tools::Processor;
+
+#[test]
+fn test_ddl_pgsql_round_trip() {
+    let sql = read_file_into_string("tests/fixtures/proc_pgsql.sql");
+    let loader = Loader::new_from_sql(&sql).expect("to read SQL");
+    assert_eq!(loader.warnings(), &Vec::<String>::new());
+    let proc = Processor::new_with_objects(loader.objects(), None);
+    assert_eq!(proc.join_sql_statements(), sql);
+}
+
+#[test]
+fn test_ddl_mysql_sqlite() {
+    let loader = Loader::new_from_sql_file("tests/fixtures/proc_mysql.sql").expect("to read SQL");
+    let cache = loader.objects().iter().filter_map(|o| o.downcast_ref::<Table>()).nth(1).expect("cache table");
+    assert_eq!(cache.fields[0].attributes.dtype, FieldType::AutoInc);
+    let loader = Loader::new_from_sql_file("tests/fixtures/proc_sqlite.sql").expect("to read SQL");
+    let proc = Processor::new_with_objects(loader.objects(), Some(Box::new(Sqlite{})));
+    assert_eq!(proc.join_sql_statements(), read_file_into_string("tests/fixtures/proc_sqlite.sql"));
+}
+
+#[test]
+fn test_ddl_constraints_and_warnings() {
+    let sql = "
+-- legacy dump
+CREATE SCHEMA IF NOT EXISTS sales;
+CREATE TABLE sales.orders (
+  id bigint GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+  customer_id int NOT NULL REFERENCES sales.customers (id) ON DELETE CASCADE,
+  total numeric(10,2) DEFAULT 0 CHECK (total >= 0),
+  created_at timestamp with time zone DEFAULT now(),
+  code varchar(20) COMMENT 'Order code',
+  CONSTRAINT orders_code_uk UNIQUE (code),
+  FOREIGN KEY (code) REFERENCES sales.codes (code) DEFERRABLE INITIALLY DEFERRED
+) ENGINE=InnoDB COMMENT='Orders';
+CREATE UNIQUE INDEX orders_customer_idx ON sales.orders USING btree (customer_id);
+COMMENT ON COLUMN sales.orders.total IS 'Total amount';
+GRANT SELECT, INSERT ON sales.orders TO ro_user, rw_user;
+CREATE VIEW sales.v AS SELECT 1;
+";
+    let loader = Loader::new_from_sql(sql).expect("to read SQL");
+    assert_eq!(loader.warnings(), &vec![
+        "clause 'CHECK(total>=0)' of sales.orders.total skipped".to_owned(),
+        "type 'timestamp with time zone' of sales.orders.created_at read as Txt".to_owned(),
+        "table options 'ENGINE=InnoDB' of sales.orders skipped".to_owned(),
+        "unique index orders_customer_idx read as the non unique index orders_customer_id_idx".to_owned(),
+        "statement not handled: CREATE VIEW sales.v AS...".to_owned(),
+    ]);
+    let objs = loader.objects();
+    assert_eq!(objs.len(), 4);
+    let schema = objs[0].downcast_ref::<Schema>().expect("a schema");
+    assert_eq!(schema.owner, "CURRENT_USER");
+    let table = objs[1].downcast_ref::<Table>().expect("a table");
+    assert_eq!(table.comment.as_deref(), Some("Orders"));
+    let id = &table.fields[0].attributes;
+    assert_eq!(id.dtype, FieldType::AutoInc);
+    assert_eq!(id.identity, Some(Identity::new(IdentityGeneration::Always, IdentityWidth::BigInt)));
+    assert!(id.primary_key);
+    assert_eq!(table.fields[2].attributes.dtype, FieldType::Dbl);
+    assert_eq!(table.fields[2].attributes.comment.as_deref(), Some("Total amount"));
+    assert_eq!(table.fields[3].attributes.defval.as_deref(), Some("now()"));
+    assert!(table.fields[4].attributes.unique);
+    let fks = table.foreign_keys();
+    assert_eq!(fks.len(), 2);
+    assert_eq!(fks[0].on_delete, FKOn::Cascade);
+    assert!(fks[1].deferrable && fks[1].initially_deferred);
+    let grants:Vec<&Grant> = objs.iter().filter_map(|o| o.downcast_ref::<Grant>()).collect();
+    assert_eq!(grants.len(), 1);
+    assert_eq!(grants[0].to, vec!["ro_user", "rw_user"]);
+    assert_eq!(grants[0].permissions, vec![GrantType::Select, GrantType::Insert]);
+}
+
+#[test]
+fn test_ddl_alter_owner_with_more_clauses() {
+    let loader = Loader::new_from_sql("ALTER TABLE sales.orders OWNER TO rw_user, ADD COLUMN note text;").expect("to read SQL");
+    assert!(loader.objects().is_empty());
+    assert_eq!(loader.warnings().len(), 1);
+    assert!(loader.warnings()[0].starts_with("statement not handled: ALTER TABLE"), "{:?}", loader.warnings());
+}
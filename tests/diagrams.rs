@@ -0,0 +1,90 @@
+use dml_tools::diagrams::*;
+use dml_tools::Loader;
+use dml_tools::Processor;
+
+const DIAGRAM_YAML: &str = "
+- tag: Table
+  path: { schema: sales, name: customers }
+  fields:
+  - name: id
+    attributes: { type: int, primary_key: true, empty: false }
+  - name: email
+    attributes: { unique: true }
+- tag: Table
+  path: { schema: sales, name: orders }
+  fields:
+  - name: id
+    attributes: { type: int, primary_key: true, empty: false }
+  - name: customer_id
+    attributes:
+      type: int
+      empty: false
+      references: { table: sales.customers, field: id }
+- tag: Table
+  path: { schema: audit, name: log }
+  fields:
+  - name: message
+    attributes: {}
+";
+
+fn diagram_output(format:DiagramFormat, options:DiagramOptions) -> String {
+    let loader = Loader::new(DIAGRAM_YAML).expect("to load tables");
+    let proc = Processor::new_with_objects(loader.objects(), None);
+    let output = Diagram::from_processor(&proc).with_options(options).render(format);
+    output
+}
+
+#[test]
+fn test_diagram_mermaid() {
+    let options = DiagramOptions { exclude: vec!["audit.*".to_owned()], ..Default::default() };
+    assert_eq!(diagram_output(DiagramFormat::Mermaid, options), "erDiagram
+    sales_customers {
+        int id PK
+        text email UK
+    }
+    sales_orders {
+        int id PK
+        int customer_id FK
+    }
+    sales_customers ||--o{ sales_orders : \"customer_id\"
+");
+}
+
+#[test]
+fn test_diagram_dot() {
+    let options = DiagramOptions { group_by_schema: true, ..Default::default() };
+    assert_eq!(diagram_output(DiagramFormat::Dot, options), "digraph er {
+  rankdir=LR;
+  node [shape=record];
+  subgraph cluster_sales {
+    label=\"sales\";
+    sales_customers [label=\"{sales.customers|id : int PK\\lemail : text UK\\l}\"];
+    sales_orders [label=\"{sales.orders|id : int PK\\lcustomer_id : int FK\\l}\"];
+  }
+  subgraph cluster_audit {
+    label=\"audit\";
+    audit_log [label=\"{audit.log|message : text\\l}\"];
+  }
+  sales_orders -> sales_customers [label=\"customer_id\"];
+}
+");
+}
+
+#[test]
+fn test_diagram_plantuml() {
+    let options = DiagramOptions { include: vec!["orders".to_owned(), "sales.customers".to_owned()], ..Default::default() };
+    assert_eq!(diagram_output(DiagramFormat::PlantUml, options), "@startuml
+entity \"sales.customers\" as sales_customers {
+  * id : int <<PK>>
+  --
+  email : text <<UK>>
+}
+entity \"sales.orders\" as sales_orders {
+  * id : int <<PK>>
+  --
+  * customer_id : int <<FK>>
+}
+sales_orders }o--|| sales_customers : customer_id
+@enduml
+");
+}
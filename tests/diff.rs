@@ -0,0 +1,207 @@
+use dml_tools::diff::*;
+use dml_tools::type_writers::*;
+use dml_tools::Loader;
+
+const OLD_YAML: &str = "
+- tag: Schema
+  name: shop
+  owner: shop_owner
+- tag: Table
+  path: { schema: shop, name: customers }
+  fields:
+  - name: id
+    attributes: { type: int, primary_key: true, empty: false }
+  - name: name
+    attributes: { empty: false }
+  - name: phone
+    attributes: {}
+  fks: ~
+- tag: Table
+  path: { schema: shop, name: orders }
+  fields:
+  - name: id
+    attributes: { type: int, primary_key: true, empty: false }
+  - name: customer
+    attributes: { type: int }
+  - name: total
+    attributes: { type: int }
+  fks: ~
+- tag: View
+  path: { schema: shop, name: big_orders, otype: View }
+  query: SELECT * FROM shop.orders WHERE total > 100
+";
+
+const NEW_YAML: &str = "
+- tag: Schema
+  name: shop
+  owner: shop_owner
+- tag: Table
+  path: { schema: shop, name: customers }
+  comment: Buyers
+  fields:
+  - name: id
+    attributes: { type: int, primary_key: true, empty: false }
+  - name: name
+    attributes: { empty: false }
+  - name: email
+    attributes: { unique: true }
+  fks: ~
+- tag: Table
+  path: { schema: shop, name: orders }
+  fields:
+  - name: id
+    attributes: { type: int, primary_key: true, empty: false }
+  - name: customer
+    attributes: { type: int, empty: false, index: true, references: { table: shop.customers, field: id } }
+  - name: total
+    attributes: { type: double, defval: '0' }
+  fks: ~
+- tag: Table
+  path: { schema: shop, name: payments }
+  fields:
+  - name: id
+    attributes: { type: int, primary_key: true, empty: false }
+  fks: ~
+- tag: Grant
+  permission: Select
+  to: reports
+  on: { schema: shop, name: payments, otype: Table }
+";
+
+#[test]
+fn test_diff_changes() {
+    let old = Loader::new(OLD_YAML).unwrap();
+    let new = Loader::new(NEW_YAML).unwrap();
+    let diff = SchemaDiff::from_loaders(&old, &new);
+    let changes:Vec<String> = diff.changes.iter().map(|c| c.to_string()).collect();
+    assert_eq!(changes, vec![
+        "drop View shop.big_orders",
+        "drop field shop.customers.phone",
+        "add field shop.customers.email",
+        "add constraint customers_email_uk",
+        "comment table shop.customers",
+        "alter field shop.orders.customer",
+        "alter field shop.orders.total",
+        "create index orders_customer_idx",
+        "create table shop.payments",
+        "create Grant",
+        "add constraint orders_customers_customer_fk",
+    ]);
+    assert!(SchemaDiff::from_loaders(&new, &new).is_empty());
+}
+
+#[test]
+fn test_diff_pgsql() {
+    let old = Loader::new(OLD_YAML).unwrap();
+    let new = Loader::new(NEW_YAML).unwrap();
+    let diff = SchemaDiff::from_loaders(&old, &new);
+    let tw = Postgresql{};
+    assert_eq!(diff.up_sql(&tw).unwrap().join("\n"), "DROP VIEW shop.big_orders;
+ALTER TABLE shop.customers DROP COLUMN phone;
+ALTER TABLE shop.customers ADD COLUMN email text NULL;
+ALTER TABLE shop.customers ADD CONSTRAINT customers_email_uk UNIQUE (email);
+COMMENT ON TABLE shop.customers IS 'Buyers';
+ALTER TABLE shop.orders ALTER COLUMN customer SET NOT NULL;
+ALTER TABLE shop.orders ALTER COLUMN total TYPE double precision;
+ALTER TABLE shop.orders ALTER COLUMN total SET DEFAULT 0;
+CREATE INDEX orders_customer_idx ON shop.orders USING btree (customer);
+CREATE TABLE shop.payments (
+  id int NOT NULL,
+  CONSTRAINT payments_id_pk PRIMARY KEY (id)
+);
+GRANT SELECT ON TABLE shop.payments TO reports;
+ALTER TABLE shop.orders ADD CONSTRAINT orders_customers_customer_fk FOREIGN KEY (customer) REFERENCES shop.customers (id) ON DELETE RESTRICT ON UPDATE RESTRICT;");
+    assert_eq!(diff.down_sql(&tw).unwrap().join("\n"), "ALTER TABLE shop.orders DROP CONSTRAINT orders_customers_customer_fk;
+REVOKE SELECT ON TABLE shop.payments FROM reports;
+DROP TABLE shop.payments;
+DROP INDEX shop.orders_customer_idx;
+ALTER TABLE shop.orders ALTER COLUMN total TYPE int;
+ALTER TABLE shop.orders ALTER COLUMN total DROP DEFAULT;
+ALTER TABLE shop.orders ALTER COLUMN customer DROP NOT NULL;
+COMMENT ON TABLE shop.customers IS NULL;
+ALTER TABLE shop.customers DROP CONSTRAINT customers_email_uk;
+ALTER TABLE shop.customers DROP COLUMN email;
+ALTER TABLE shop.customers ADD COLUMN phone text NULL;
+CREATE VIEW shop.big_orders AS
+SELECT * FROM shop.orders WHERE total > 100;");
+}
+
+#[test]
+fn test_diff_mysql_sqlite() {
+    let old = Loader::new(OLD_YAML).unwrap();
+    let new = Loader::new(NEW_YAML).unwrap();
+    let diff = SchemaDiff::from_loaders(&old, &new);
+    let up = diff.up_sql(&Mysql{}).unwrap();
+    assert_eq!(up[3], "ALTER TABLE shop.customers ADD CONSTRAINT customers_email_uk UNIQUE (email);");
+    assert_eq!(up[4], "ALTER TABLE shop.customers COMMENT='Buyers';");
+    assert_eq!(up[6], "ALTER TABLE shop.orders MODIFY COLUMN total double NULL DEFAULT 0;");
+    let down = diff.down_sql(&Mysql{}).unwrap();
+    assert_eq!(down[0], "ALTER TABLE shop.orders DROP FOREIGN KEY orders_customers_customer_fk;");
+    assert_eq!(down[3], "DROP INDEX orders_customer_idx ON shop.orders;");
+}
+
+#[test]
+fn test_diff_sqlite_rebuild() {
+    let old = Loader::new(OLD_YAML).unwrap();
+    let new = Loader::new(NEW_YAML).unwrap();
+    let diff = SchemaDiff::from_loaders(&old, &new);
+    let up = diff.up_sql(&Sqlite{}).unwrap();
+    assert_eq!(up.len(), 4);
+    assert_eq!(up[0], "DROP VIEW big_orders;");
+    assert_eq!(up[1], "PRAGMA foreign_keys=off;
+BEGIN;
+-- Buyers
+CREATE TABLE new_customers (
+  id integer NOT NULL,
+  name text NOT NULL,
+  email text NULL,
+  CONSTRAINT customers_id_pk PRIMARY KEY (id),
+  CONSTRAINT customers_email_uk UNIQUE (email)
+);
+INSERT INTO new_customers (id, name) SELECT id, name FROM customers;
+DROP TABLE customers;
+ALTER TABLE new_customers RENAME TO customers;
+PRAGMA foreign_key_check;
+COMMIT;
+PRAGMA foreign_keys=on;");
+    assert!(up[2].starts_with("CREATE TABLE payments ("));
+    // the orders rebuild takes the place of its foreign key, after the tables it references
+    assert!(up[3].contains("CONSTRAINT orders_customers_customer_fk FOREIGN KEY (customer) REFERENCES customers (id)"));
+    assert!(up[3].contains("INSERT INTO new_orders (id, customer, total) SELECT id, customer, total FROM orders;"));
+    assert!(up[3].ends_with("ALTER TABLE new_orders RENAME TO orders;\nCREATE INDEX orders_customer_idx ON orders (customer);\nPRAGMA foreign_key_check;\nCOMMIT;\nPRAGMA foreign_keys=on;"));
+
+    let down = diff.down_sql(&Sqlite{}).unwrap();
+    assert_eq!(down.len(), 4);
+    assert_eq!(down[0], "DROP TABLE payments;");
+    assert!(down[1].contains("  total integer NULL,\n  CONSTRAINT orders_id_pk PRIMARY KEY (id)\n);"));
+    assert!(! down[1].contains("CREATE INDEX"));
+    assert!(down[2].contains("  phone text NULL,"));
+    assert!(down[2].contains("INSERT INTO new_customers (id, name) SELECT id, name FROM customers;"));
+    assert!(down[3].starts_with("CREATE VIEW big_orders AS"));
+}
+
+
+#[test]
+fn test_diff_sqlite_rebuild_new_not_null_field() {
+    let old = Loader::new("
+- tag: Table
+  path: { name: items }
+  fields:
+  - name: id
+    attributes: { type: int, primary_key: true, empty: false }
+  fks: ~
+").unwrap();
+    let new = Loader::new("
+- tag: Table
+  path: { name: items }
+  fields:
+  - name: id
+    attributes: { type: int, primary_key: true, empty: false }
+  - name: qty
+    attributes: { type: int, empty: false, unique: true }
+  fks: ~
+").unwrap();
+    let diff = SchemaDiff::from_loaders(&old, &new);
+    let err = diff.up_sql(&Sqlite{}).expect_err("a NOT NULL field without default");
+    assert_eq!(err.to_string(), "items can not be rebuilt: new NOT NULL field qty has no default value");
+}
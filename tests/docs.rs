@@ -0,0 +1,99 @@
+use dml_tools::docs::*;
+use dml_tools::type_writers::*;
+use dml_tools::Loader;
+use dml_tools::Processor;
+
+const DOCS_YAML: &str = "
+- tag: Schema
+  name: sales
+  owner: rw_user
+- tag: Table
+  path: { schema: sales, name: customers }
+  comment: Our customers
+  fields:
+  - name: id
+    attributes: { type: auto_increment, primary_key: true, empty: false }
+  - name: email
+    attributes: { unique: true, comment: 'Contact <email>' }
+- tag: Table
+  path: { schema: sales, name: orders }
+  fields:
+  - name: id
+    attributes: { type: int, primary_key: true, empty: false }
+  - name: customer_id
+    attributes:
+      type: int
+      index: true
+      references: { table: sales.customers, field: id, on_delete: cascade }
+  - name: status
+    attributes: { defval: new, empty: false }
+- tag: Grant
+  permission: Select
+  to: ro_user
+  on: { schema: sales, name: orders }
+- tag: Owner
+  to: rw_user
+  of: { schema: sales, name: orders }
+";
+
+const DOCS_MD: &str = "# Data dictionary
+
+## Schema sales
+
+Owner: rw_user
+
+<a id=\"sales-customers\"></a>
+### sales.customers
+
+Our customers
+
+| Column | Type | Nullable | Default | Keys | Comment |
+|---|---|---|---|---|---|
+| id | serial | NO |  | PK |  |
+| email | text | YES |  | UK | Contact <email> |
+
+<a id=\"sales-orders\"></a>
+### sales.orders
+
+| Column | Type | Nullable | Default | Keys | Comment |
+|---|---|---|---|---|---|
+| id | int | NO |  | PK |  |
+| customer_id | int | YES |  | FK [sales.customers](#sales-customers).id |  |
+| status | text | NO | new |  |  |
+
+**Indexes**
+
+- orders_customer_id_idx (customer_id)
+
+**Foreign keys**
+
+- (customer_id) → [sales.customers](#sales-customers) (id) ON DELETE CASCADE ON UPDATE RESTRICT
+
+**Grants**
+
+- SELECT to ro_user
+
+**Owner**
+
+- rw_user
+";
+
+#[test]
+fn test_docs_markdown() {
+    let loader = Loader::new(DOCS_YAML).expect("to load objects");
+    let proc = Processor::new_with_objects(loader.objects(), None);
+    let dict = DataDictionary::from_processor(&proc);
+    assert_eq!(dict.to_markdown(), DOCS_MD);
+}
+
+#[test]
+fn test_docs_html() {
+    let loader = Loader::new(DOCS_YAML).expect("to load objects");
+    let dict = DataDictionary::from_loader(&loader, &Mysql{});
+    let html = dict.to_html();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<h3 id=\"sales-orders\">sales.orders</h3>"));
+    assert!(html.contains("<td>id</td><td>integer auto_increment</td><td>NO</td>"));
+    assert!(html.contains("<td>Contact &lt;email&gt;</td>"));
+    assert!(html.contains("FK <a href=\"#sales-customers\">sales.customers</a>.id"));
+}
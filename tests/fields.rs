@@ -11,32 +11,32 @@ fn test_fields() {
     let t = fields.basic.get("id").expect("to get id");
     assert_eq!(t, &FieldAttributes{
         dtype:FieldType::Txt, unique:false, empty: false, roster: false, defval: None,
-        primary_key:true, index: false, only_db:false, meta_name: None,
+        primary_key:true, index: false, only_db:false, meta_name: None, identity: None, generated: None, references: None, comment: None,
     });
     let t = fields.basic.get("pk").expect("to get pk");
     assert_eq!(t, &FieldAttributes{
         dtype:FieldType::Txt, unique:false, empty: true, roster: false, defval: None,
-        primary_key:false, index: false, only_db:false, meta_name: None,
+        primary_key:false, index: false, only_db:false, meta_name: None, identity: None, generated: None, references: None, comment: None,
     });
     let t = fields.basic.get("ws").expect("to get ws");
     assert_eq!(t, &FieldAttributes{
         dtype:FieldType::Txt, unique: false, empty: true, roster: false, defval: None,
-        primary_key:true, index: false, only_db:false, meta_name: None,
+        primary_key:true, index: false, only_db:false, meta_name: None, identity: None, generated: None, references: None, comment: None,
     });
     let t = fields.basic.get("gallo").expect("to get gallo");
     assert_eq!(t, &FieldAttributes{
         dtype:FieldType::Int, unique: false, empty: true, roster: true, defval: None,
-        primary_key:false, index: true, only_db:false, meta_name: None,
+        primary_key:false, index: true, only_db:false, meta_name: None, identity: None, generated: None, references: None, comment: None,
     });
     let t = fields.basic.get("vivo").expect("to get vivo");
     assert_eq!(t, &FieldAttributes{
         dtype:FieldType::Bool, unique: false, empty: true, roster: false, defval: Some("true".into()),
-        primary_key:false, index: true, only_db:false, meta_name: None,
+        primary_key:false, index: true, only_db:false, meta_name: None, identity: None, generated: None, references: None, comment: None,
     });
     let t = fields.basic.get("tel").expect("to get tel");
     assert_eq!(t, &FieldAttributes{
         dtype:FieldType::Txt, unique: false, empty: true, roster: false, defval: None,
-        primary_key:false, index: false, only_db:false, meta_name: Some("TEL".into()),
+        primary_key:false, index: false, only_db:false, meta_name: Some("TEL".into()), identity: None, generated: None, references: None, comment: None,
     });
 
     assert!(fields.sensitization.is_some());
@@ -45,17 +45,37 @@ fn test_fields() {
     let t = sens.get("hog_sens").expect("to get hog_sens");
     assert_eq!(t, &FieldAttributes{
         dtype:FieldType::Int, unique: false, empty: true, roster: true, defval: None,
-        primary_key:false, index: false, only_db:false, meta_name: None,
+        primary_key:false, index: false, only_db:false, meta_name: None, identity: None, generated: None, references: None, comment: None,
     });
     let t = sens.get("pct_life").expect("to get pct_life");
     assert_eq!(t, &FieldAttributes{
         dtype:FieldType::Dbl, unique: false, empty: true, roster: false, defval: None,
-        primary_key:false, index: false, only_db:false, meta_name: None,
+        primary_key:false, index: false, only_db:false, meta_name: None, identity: None, generated: None, references: None, comment: None,
     });
     let t = sens.get("id").expect("to get id");
     assert_eq!(t, &FieldAttributes{
         dtype:FieldType::AutoInc, unique: false, empty: true, roster: false, defval: None,
-        primary_key:false, index: false, only_db:false, meta_name: None,
+        primary_key:false, index: false, only_db:false, meta_name: None, identity: None, generated: None, references: None, comment: None,
     });
 
 }
+
+#[test]
+fn test_generated_fields() {
+    use dml_tools::type_writers::*;
+    let full_name = Field::new("full_name", &FieldAttributes::new_generated(FieldType::Txt,
+        "first_name || ' ' || last_name", GeneratedStorage::Virtual));
+    assert_eq!(full_name.to_sql(&Postgresql{}), "full_name text GENERATED ALWAYS AS (first_name || ' ' || last_name) STORED NULL");
+    assert_eq!(full_name.to_sql(&Mysql{}), "full_name text GENERATED ALWAYS AS (first_name || ' ' || last_name) VIRTUAL NULL");
+    assert_eq!(full_name.to_sql(&Sqlite{}), "full_name text GENERATED ALWAYS AS (first_name || ' ' || last_name) VIRTUAL NULL");
+    assert!(full_name.read_only());
+    assert!(!Field::new("first_name", &FieldAttributes::new(FieldType::Txt)).read_only());
+
+    let attrs: FieldAttributes = dml_tools::util::read_yaml_from_string("
+type: int
+generated:
+  expression: price * quantity
+").expect("to read generated field");
+    assert_eq!(attrs.generated, Some(Generated{ expression: "price * quantity".into(), storage: GeneratedStorage::Stored }));
+    assert!(attrs.read_only());
+}
@@ -25,5 +25,5 @@ CREATE TABLE cache (
   date_created text NOT NULL,
   date_updated text NOT NULL,
   otro integer NULL,
-  CONSTRAINT cache_workspace_survey_id_pk PRIMARY KEY (workspace,survey_id)
+  CONSTRAINT cache_workspace_survey_id_uk UNIQUE (workspace,survey_id)
 );
\ No newline at end of file
@@ -0,0 +1,110 @@
+use dml_tools::sql::*;
+use dml_tools::type_writers::*;
+use dml_tools::Loader;
+
+fn fk_with_options() -> ForeignKey {
+    let mut fk = ForeignKey::new(
+        &ObjectPath::new_table("demo", "orders"), &vec!["customer_id".to_owned()],
+        &ObjectPath::new_table("demo", "customers"), &vec!["id".to_owned()]);
+    fk.on_delete = FKOn::SetNull;
+    fk.on_update = FKOn::SetDefault;
+    fk.deferrable = true;
+    fk.initially_deferred = true;
+    fk.match_type = Some(FKMatch::Full);
+    fk
+}
+
+#[test]
+fn test_fk_options_per_writer() {
+    let fk = fk_with_options();
+    assert_eq!(fk.to_sql(&Postgresql{}),
+        "CONSTRAINT orders_customers_customer_id_fk FOREIGN KEY (customer_id) REFERENCES demo.customers (id) MATCH FULL ON DELETE SET NULL ON UPDATE SET DEFAULT DEFERRABLE INITIALLY DEFERRED");
+    assert!(fk.unsupported_clauses(&Postgresql{}).is_empty());
+
+    assert_eq!(fk.to_sql(&Mysql{}),
+        "CONSTRAINT orders_customers_customer_id_fk FOREIGN KEY (customer_id) REFERENCES demo.customers (id) ON DELETE SET NULL");
+    assert_eq!(fk.unsupported_clauses(&Mysql{}), vec!["ON UPDATE SET DEFAULT", "MATCH FULL", "DEFERRABLE"]);
+
+    assert_eq!(fk.to_sql(&Sqlite{}),
+        "CONSTRAINT orders_customers_customer_id_fk FOREIGN KEY (customer_id) REFERENCES customers (id) ON DELETE SET NULL ON UPDATE SET DEFAULT DEFERRABLE INITIALLY DEFERRED");
+    assert_eq!(fk.unsupported_clauses(&Sqlite{}), vec!["MATCH FULL"]);
+}
+
+#[test]
+fn test_fk_options_from_yaml() {
+    let yaml = "
+table: { schema: demo, name: orders }
+fields: [customer_id]
+ref_table: { schema: demo, name: customers }
+ref_fields: [id]
+on_delete: NoAction
+deferrable: true
+match_type: Simple
+";
+    let fk: ForeignKey = dml_tools::util::read_yaml_from_string(yaml).expect("to read fk");
+    assert_eq!(fk.on_delete, FKOn::NoAction);
+    assert_eq!(fk.on_update, FKOn::Restrict);
+    assert_eq!(fk.to_sql(&Postgresql{}),
+        "CONSTRAINT orders_customers_customer_id_fk FOREIGN KEY (customer_id) REFERENCES demo.customers (id) MATCH SIMPLE ON DELETE NO ACTION ON UPDATE RESTRICT DEFERRABLE");
+}
+
+#[test]
+fn test_fk_set_null_on_not_null_field() {
+    let fields = vec![
+        Field::new("id", &FieldAttributes::new_pk(FieldType::Int)),
+        Field::new("customer_id", &FieldAttributes::new_nn(FieldType::Int)),
+    ];
+    let table = Table::new(&ObjectPath::new_table("demo", "orders"), fields, Some(vec![fk_with_options()]));
+    let err = table.validate().expect_err("SET NULL on a NOT NULL field");
+    assert_eq!(err.to_string(), "orders_customers_customer_id_fk uses SET NULL on NOT NULL field demo.orders.customer_id");
+
+    let err = Loader::new("
+- tag: Table
+  path: { schema: demo, name: orders }
+  fields:
+  - name: customer_id
+    attributes: { type: int, empty: false, references: { table: demo.customers, field: id, on_delete: set_null } }
+  fks: ~
+").err().expect("loader to fail");
+    assert!(err.to_string().contains("uses SET NULL on NOT NULL field demo.orders.customer_id"));
+}
+
+const REFERENCES_SQL: &str = "CREATE TABLE demo.orders (
+  id int NOT NULL,
+  customer_id int NULL,
+  CONSTRAINT orders_id_pk PRIMARY KEY (id),
+  CONSTRAINT orders_customers_customer_id_fk FOREIGN KEY (customer_id) REFERENCES sales.customers (id) ON DELETE CASCADE ON UPDATE RESTRICT
+);";
+
+#[test]
+fn test_field_references_from_code() {
+    let mut cust = FieldAttributes::new_ref(FieldType::Int, "sales.customers", "id");
+    if let Some(r) = cust.references.as_mut() {
+        r.on_delete = FKOn::Cascade
+    }
+    let fields = vec![
+        Field::new("id", &FieldAttributes::new_pk(FieldType::Int)),
+        Field::new("customer_id", &cust),
+    ];
+    let table = Table::new(&ObjectPath::new_table("demo", "orders"), fields, None);
+    assert_eq!(table.foreign_keys().len(), 1);
+    assert_eq!(table.to_sql(&Postgresql{}), REFERENCES_SQL);
+}
+
+#[test]
+fn test_field_references_from_yaml() {
+    let yaml = "
+- tag: Table
+  path: { schema: demo, name: orders }
+  fields:
+  - name: id
+    attributes: { type: int, primary_key: true, empty: false }
+  - name: customer_id
+    attributes:
+      type: int
+      references: { table: sales.customers, field: id, on_delete: cascade }
+";
+    let loader = dml_tools::Loader::new(yaml).expect("to load table");
+    let proc = dml_tools::Processor::new_with_objects(loader.objects(), None);
+    assert_eq!(proc.join_sql_statements(), REFERENCES_SQL);
+}
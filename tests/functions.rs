@@ -0,0 +1,98 @@
+use dml_tools::sql::*;
+use dml_tools::type_writers::*;
+use dml_tools::Processor;
+use dml_tools::macros::*;
+
+fn user_count() -> Function {
+    let mut func = Function::new(&ObjectPath::new_function("my_schema", "user_count"), Some("int"),
+        "BEGIN\n  RETURN (SELECT count(*) FROM my_schema.users WHERE role = p_role);\nEND;");
+    let mut active = FunctionArg::new("p_active", "bool");
+    active.default = Some("true".to_owned());
+    func.args = vec![FunctionArg::new("p_role", "text"), active];
+    func.volatility = Some(Volatility::Stable);
+    func.security_definer = true;
+    func
+}
+
+fn function_sqls(type_writer:Box<dyn TypeWriter>) -> Vec<String> {
+    let func = user_count();
+    let mut proc = Processor::new(Some(type_writer));
+    proc.add(&func);
+    add_grant!(proc, GrantType::Execute, "ro_user", &func.path);
+    proc.sql_statements()
+}
+
+#[test]
+fn test_function_pgsql() {
+    assert_eq!(function_sqls(Box::new(Postgresql{})), vec![
+"CREATE OR REPLACE FUNCTION my_schema.user_count(p_role text, p_active bool DEFAULT true) RETURNS int
+LANGUAGE plpgsql STABLE SECURITY DEFINER
+AS $$
+BEGIN
+  RETURN (SELECT count(*) FROM my_schema.users WHERE role = p_role);
+END;
+$$;",
+"GRANT EXECUTE ON FUNCTION my_schema.user_count TO ro_user;",
+    ]);
+}
+
+#[test]
+fn test_function_mysql() {
+    assert_eq!(function_sqls(Box::new(Mysql{})), vec![
+"DELIMITER $$
+DROP FUNCTION IF EXISTS my_schema.user_count$$
+CREATE FUNCTION my_schema.user_count(p_role text, p_active bool) RETURNS int
+NOT DETERMINISTIC READS SQL DATA
+SQL SECURITY DEFINER
+BEGIN
+  RETURN (SELECT count(*) FROM my_schema.users WHERE role = p_role);
+END;$$
+DELIMITER ;",
+"GRANT EXECUTE ON FUNCTION my_schema.user_count TO ro_user;",
+    ]);
+}
+
+#[test]
+fn test_function_sqlite() {
+    assert!(function_sqls(Box::new(Sqlite{})).is_empty());
+}
+
+#[test]
+fn test_procedure_pgsql() {
+    let mut procedure = Function::new(&ObjectPath::new_procedure("my_schema", "purge"), None, "DELETE FROM my_schema.logs");
+    procedure.language = "sql".to_owned();
+    let mut arg = FunctionArg::new("p_days", "int");
+    arg.mode = Some(ArgMode::In);
+    procedure.args.push(arg);
+    assert_eq!(procedure.to_sql(&Postgresql{}), "CREATE OR REPLACE PROCEDURE my_schema.purge(IN p_days int)
+LANGUAGE sql
+AS $$
+DELETE FROM my_schema.logs
+$$;");
+}
+
+#[test]
+fn test_functions_from_yaml() {
+    let objs:Vec<Box<dyn DBObject>> = dml_tools::util::read_yaml_from_string("
+- tag: Function
+  path: { schema: public, name: f }
+  returns: int
+  language: sql
+  body: SELECT 1
+- tag: Function
+  path: { schema: public, name: p }
+  procedure: true
+  language: sql
+  body: DELETE FROM public.logs
+").expect("to read functions");
+    let func = objs[0].downcast_ref::<Function>().expect("a function");
+    assert_eq!(func.path.otype, ObjectType::Function);
+    assert_eq!(func.to_sql(&Postgresql{}), "CREATE OR REPLACE FUNCTION public.f() RETURNS int
+LANGUAGE sql
+AS $$
+SELECT 1
+$$;");
+    assert_eq!(func.drop_sql(&Postgresql{}), "DROP FUNCTION public.f;");
+    assert!(objs[1].downcast_ref::<Function>().expect("a procedure").is_procedure());
+    assert_eq!(objs[1].drop_sql(&Postgresql{}), "DROP PROCEDURE public.p;");
+}
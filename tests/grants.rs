@@ -0,0 +1,149 @@
+use dml_tools::diff::*;
+use dml_tools::macros::*;
+use dml_tools::sql::*;
+use dml_tools::type_writers::*;
+use dml_tools::Loader;
+use dml_tools::Processor;
+
+#[test]
+fn test_grant_options_and_revoke() {
+    let on = ObjectPath::new_table("shop", "items");
+    let grant = Grant {
+        with_grant_option: true,
+        granted_by: Some("admin".to_owned()),
+        ..Grant::new(GrantType::Select, "reports", &on)
+    };
+    assert_eq!(grant.to_sql(&Postgresql{}), "GRANT SELECT ON TABLE shop.items TO reports WITH GRANT OPTION GRANTED BY admin;");
+    assert_eq!(grant.to_sql(&Mysql{}), "GRANT SELECT ON TABLE shop.items TO reports WITH GRANT OPTION;");
+    assert_eq!(grant.drop_sql(&Postgresql{}), "REVOKE SELECT ON TABLE shop.items FROM reports GRANTED BY admin;");
+    assert_eq!(grant.to_sql(&Sqlite{}), "");
+
+    let revoke = Revoke { grant_option_for: true, cascade: true, ..Revoke::new(GrantType::All, "upd_user", &on) };
+    assert_eq!(revoke.to_sql(&Postgresql{}), "REVOKE GRANT OPTION FOR ALL ON TABLE shop.items FROM upd_user CASCADE;");
+    assert_eq!(revoke.drop_sql(&Postgresql{}), "GRANT ALL ON TABLE shop.items TO upd_user WITH GRANT OPTION;");
+
+    let mut proc = Processor::new(None);
+    add_revoke!(proc, GrantType::Delete, "ro_user", &on);
+    assert_eq!(proc.sql_statements(), vec!["REVOKE DELETE ON TABLE shop.items FROM ro_user;"]);
+}
+
+#[test]
+fn test_grant_yaml_and_ddl() {
+    let loader = Loader::new("
+- tag: Grant
+  permission: Update
+  to: clerks
+  on: { schema: shop, name: items, otype: Table }
+  with_grant_option: true
+- tag: Revoke
+  permission: Usage
+  from: public
+  on: { name: shop, otype: Schema }
+").unwrap();
+    let proc = Processor::new_with_objects(loader.objects(), None);
+    assert_eq!(proc.sql_statements(), vec![
+        "GRANT UPDATE ON TABLE shop.items TO clerks WITH GRANT OPTION;",
+        "REVOKE USAGE ON SCHEMA shop FROM public;",
+    ]);
+
+    let loader = Loader::new_from_sql("GRANT SELECT, INSERT ON shop.items TO clerks WITH GRANT OPTION GRANTED BY admin;
+REVOKE GRANT OPTION FOR DELETE ON TABLE shop.items FROM clerks, temps RESTRICT;").unwrap();
+    assert!(loader.warnings().is_empty(), "{:?}", loader.warnings());
+    let proc = Processor::new_with_objects(loader.objects(), None);
+    assert_eq!(proc.sql_statements(), vec![
+        "GRANT SELECT, INSERT ON TABLE shop.items TO clerks WITH GRANT OPTION GRANTED BY admin;",
+        "REVOKE GRANT OPTION FOR DELETE ON TABLE shop.items FROM clerks, temps;",
+    ]);
+}
+
+#[test]
+fn test_grant_lists_columns_and_all_tables() {
+    let loader = Loader::new("
+- tag: Grant
+  permission: [ Select, Update ]
+  to: [ clerks, auditors ]
+  on: { schema: shop, name: items, otype: Table }
+  columns: [ id, name ]
+- tag: Grant
+  permission: Select
+  to: reports
+  on: { name: shop, otype: Schema }
+  all_tables: true
+").unwrap();
+    let proc = Processor::new_with_objects(loader.objects(), None);
+    assert_eq!(proc.sql_statements(), vec![
+        "GRANT SELECT (id, name), UPDATE (id, name) ON TABLE shop.items TO clerks, auditors;",
+        "GRANT SELECT ON ALL TABLES IN SCHEMA shop TO reports;",
+    ]);
+    let proc = Processor::new_with_objects(loader.objects(), Some(Box::new(Mysql{})));
+    assert_eq!(proc.sql_statements()[1], "GRANT SELECT ON shop.* TO reports;");
+    let grant = loader.objects()[0].downcast_ref::<Grant>().unwrap();
+    assert_eq!(grant.drop_sql(&Postgresql{}), "REVOKE SELECT (id, name), UPDATE (id, name) ON TABLE shop.items FROM clerks, auditors;");
+    // single values are written back as such
+    let yaml = serde_yaml::to_string(loader.objects()[1].downcast_ref::<Grant>().unwrap()).unwrap();
+    assert!(yaml.contains("permission: Select\nto: reports\n"), "{yaml}");
+
+    let loader = Loader::new_from_sql("GRANT SELECT (id), REFERENCES (id) ON shop.items TO clerks;
+GRANT USAGE, SELECT ON ALL TABLES IN SCHEMA shop TO reports, clerks;").unwrap();
+    let proc = Processor::new_with_objects(loader.objects(), None);
+    assert_eq!(proc.sql_statements(), vec![
+        "GRANT SELECT (id), REFERENCES (id) ON TABLE shop.items TO clerks;",
+        "GRANT USAGE, SELECT ON ALL TABLES IN SCHEMA shop TO reports, clerks;",
+    ]);
+    let grant = Grant::new_many(vec![GrantType::Insert, GrantType::Delete], &["rw_user", "upd_user"], &ObjectPath::new_table("shop", "items"));
+    assert_eq!(grant.to_sql(&Postgresql{}), "GRANT INSERT, DELETE ON TABLE shop.items TO rw_user, upd_user;");
+}
+
+const OLD_GRANTS: &str = "
+- tag: Grant
+  permission: All
+  to: upd_user
+  on: { schema: shop, name: items, otype: Table }
+- tag: Grant
+  permission: Select
+  to: ro_user
+  on: { schema: shop, name: items, otype: Table }
+- tag: Grant
+  permission: Select
+  to: reports
+  on: { schema: shop, name: items, otype: Table }
+";
+
+const NEW_GRANTS: &str = "
+- tag: Grant
+  permission: Update
+  to: upd_user
+  on: { schema: shop, name: items, otype: Table }
+- tag: Grant
+  permission: Select
+  to: ro_user
+  on: { schema: shop, name: items, otype: Table }
+  with_grant_option: true
+";
+
+#[test]
+fn test_grant_diff() {
+    let old = Loader::new(OLD_GRANTS).unwrap();
+    let new = Loader::new(NEW_GRANTS).unwrap();
+    let diff = SchemaDiff::from_loaders(&old, &new);
+    let changes:Vec<String> = diff.changes.iter().map(|c| c.to_string()).collect();
+    assert_eq!(changes, vec![
+        "drop Grant",
+        "drop Grant",
+        "create Grant",
+        "alter grant SELECT on shop.items to ro_user",
+    ]);
+    let tw = Postgresql{};
+    assert_eq!(diff.up_sql(&tw).unwrap(), vec![
+        "REVOKE SELECT ON TABLE shop.items FROM reports;",
+        "REVOKE ALL ON TABLE shop.items FROM upd_user;",
+        "GRANT UPDATE ON TABLE shop.items TO upd_user;",
+        "GRANT SELECT ON TABLE shop.items TO ro_user WITH GRANT OPTION;",
+    ]);
+    assert_eq!(diff.down_sql(&tw).unwrap(), vec![
+        "REVOKE GRANT OPTION FOR SELECT ON TABLE shop.items FROM ro_user;",
+        "REVOKE UPDATE ON TABLE shop.items FROM upd_user;",
+        "GRANT ALL ON TABLE shop.items TO upd_user;",
+        "GRANT SELECT ON TABLE shop.items TO reports;",
+    ]);
+}
@@ -0,0 +1,69 @@
+use dml_tools::sql::*;
+use dml_tools::type_writers::*;
+use dml_tools::history::History;
+use dml_tools::Processor;
+
+fn users() -> Table {
+    let fields = vec![
+        Field::new("user_id", &FieldAttributes::new_pk(FieldType::AutoInc)),
+        Field::new("email", &FieldAttributes::new_uk(FieldType::Txt)),
+        Field::new("role", &FieldAttributes::new_nn_def(FieldType::Txt, "user")),
+    ];
+    Table::new(&ObjectPath::new_table("my_schema", "users"), fields, None)
+}
+
+fn history_sql(type_writer:Box<dyn TypeWriter>) -> String {
+    let history = History::new(&users(), type_writer.as_ref());
+    let mut processor = Processor::new(Some(type_writer));
+    for obj in history.objects() {
+        processor.add(obj);
+    }
+    processor.join_sql_statements()
+}
+
+#[test]
+fn test_history_pgsql() {
+    assert_eq!(history_sql(Box::new(Postgresql{})), r#"CREATE TABLE my_schema.users_history (
+  user_id int NULL,
+  email text NULL,
+  "role" text NULL,
+  changed_at text NOT NULL,
+  changed_by text NULL,
+  op text NOT NULL
+);
+CREATE OR REPLACE FUNCTION my_schema.users_history_fn() RETURNS trigger
+LANGUAGE plpgsql
+AS $$
+BEGIN
+  IF TG_OP = 'DELETE' THEN
+    INSERT INTO my_schema.users_history (user_id, email, "role", changed_at, changed_by, op) VALUES (OLD.user_id, OLD.email, OLD."role", CURRENT_TIMESTAMP, CURRENT_USER, TG_OP);
+    RETURN OLD;
+  END IF;
+  INSERT INTO my_schema.users_history (user_id, email, "role", changed_at, changed_by, op) VALUES (NEW.user_id, NEW.email, NEW."role", CURRENT_TIMESTAMP, CURRENT_USER, TG_OP);
+  RETURN NEW;
+END;
+$$;
+CREATE TRIGGER users_history AFTER INSERT OR UPDATE OR DELETE ON my_schema.users
+FOR EACH ROW
+EXECUTE FUNCTION my_schema.users_history_fn();"#);
+}
+
+#[test]
+fn test_history_sqlite() {
+    let sql = history_sql(Box::new(Sqlite{}));
+    assert!(sql.starts_with("CREATE TABLE users_history (\n  user_id integer NULL,"));
+    assert!(sql.contains(r#"CREATE TRIGGER users_history_delete AFTER DELETE ON users
+FOR EACH ROW
+BEGIN
+INSERT INTO users_history (user_id, email, "role", changed_at, changed_by, op) VALUES (OLD.user_id, OLD.email, OLD."role", CURRENT_TIMESTAMP, NULL, 'DELETE');
+END;"#));
+}
+
+#[test]
+fn test_history_mysql() {
+    let history = History::new(&users(), &Mysql{});
+    assert!(history.function.is_none());
+    let names:Vec<&str> = history.triggers.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["users_history_insert", "users_history_update", "users_history_delete"]);
+    assert_eq!(history.objects().len(), 4);
+}
@@ -0,0 +1,131 @@
+use dml_tools::migrations::*;
+use dml_tools::sql::*;
+use dml_tools::type_writers::Postgresql;
+use std::error::Error;
+
+/// Driver keeping the applied migrations in memory
+#[derive(Default)]
+struct MockDriver {
+    applied: Vec<AppliedMigration>,
+    executed: Vec<String>,
+    fail_on: Option<u32>,
+}
+
+impl MigrationDriver for MockDriver {
+    fn init(&mut self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+    fn applied(&mut self) -> Result<Vec<AppliedMigration>, Box<dyn Error>> {
+        Ok(self.applied.clone())
+    }
+    fn apply(&mut self, migration:&Migration) -> Result<(), Box<dyn Error>> {
+        if self.fail_on == Some(migration.version) {
+            return Err("boom".into())
+        }
+        self.executed.push(migration.up.to_owned());
+        self.applied.push(AppliedMigration {
+            version: migration.version,
+            name: migration.name.to_owned(),
+            checksum: migration.checksum(),
+            applied_at: "now".to_owned(),
+        });
+        Ok(())
+    }
+    fn revert(&mut self, migration:&Migration) -> Result<(), Box<dyn Error>> {
+        self.executed.push(migration.down.to_owned().unwrap_or_default());
+        self.applied.retain(|a| a.version != migration.version);
+        Ok(())
+    }
+}
+
+fn states(migrator:&Migrator, driver:&mut MockDriver) -> Vec<(u32, MigrationState)> {
+    migrator.status(driver).unwrap().into_iter().map(|s| (s.version, s.state)).collect()
+}
+
+#[test]
+fn test_migrations_read_dir() {
+    assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+    assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+    let migrator = Migrator::from_dir("tests/fixtures/migrations").expect("to read migrations");
+    let migrations = migrator.migrations();
+    assert_eq!(migrations.len(), 3);
+    assert_eq!(migrations[1].file_stem(), "V0002__add_email");
+    assert_eq!(migrations[1].up, "ALTER TABLE users ADD COLUMN email text;\n");
+    assert_eq!(migrations[1].down.as_deref(), Some("ALTER TABLE users DROP COLUMN email;\n"));
+    assert_eq!(migrations[2].down, None);
+    assert_eq!(record_sql(&Migration::new(4, "it's", "", None)),
+        format!("INSERT INTO _dml_tools_migrations (version, name, checksum) VALUES (4, 'it''s', '{:016x}');", fnv1a(b"")));
+}
+
+#[test]
+fn test_migrations_up_down_status() {
+    let migrator = Migrator::from_dir("tests/fixtures/migrations").unwrap();
+    let mut driver = MockDriver::default();
+    assert_eq!(states(&migrator, &mut driver), vec![
+        (1, MigrationState::Pending), (2, MigrationState::Pending), (3, MigrationState::Pending)]);
+    assert_eq!(migrator.up(&mut driver, Some(2)).unwrap(), vec![1, 2]);
+    assert_eq!(migrator.up(&mut driver, None).unwrap(), vec![3]);
+    assert_eq!(migrator.up(&mut driver, None).unwrap(), Vec::<u32>::new());
+    assert_eq!(driver.executed.len(), 3);
+
+    let err = migrator.down(&mut driver, 1).unwrap_err();
+    assert_eq!(err.to_string(), "migration V0003__index_email has no down SQL");
+    driver.applied.pop();
+    assert_eq!(migrator.down(&mut driver, 5).unwrap(), vec![2, 1]);
+    assert_eq!(driver.executed.last().unwrap(), "DROP TABLE users;\n");
+    assert!(driver.applied.is_empty());
+}
+
+#[test]
+fn test_migrations_errors() {
+    let mut migrations = vec![
+        Migration::new(1, "one", "SELECT 1;", None),
+        Migration::new(2, "two", "SELECT 2;", None),
+    ];
+    let migrator = Migrator::new(migrations.clone());
+    let mut driver = MockDriver { fail_on: Some(2), ..Default::default() };
+    let err = migrator.up(&mut driver, None).unwrap_err();
+    assert_eq!(err.to_string(), "migration V0002__two failed: boom");
+    assert_eq!(states(&migrator, &mut driver), vec![(1, MigrationState::Applied), (2, MigrationState::Pending)]);
+
+    driver.fail_on = None;
+    migrations[0].up = "SELECT 10;".to_owned();
+    migrations.remove(1);
+    migrations.push(Migration::new(3, "three", "SELECT 3;", None));
+    driver.applied.push(AppliedMigration { version: 2, name: "two".to_owned(), checksum: "".to_owned(), applied_at: "now".to_owned() });
+    let migrator = Migrator::new(migrations.clone());
+    assert_eq!(states(&migrator, &mut driver), vec![
+        (1, MigrationState::Modified), (2, MigrationState::Missing), (3, MigrationState::Pending)]);
+    let err = migrator.up(&mut driver, None).unwrap_err();
+    assert_eq!(err.to_string(), "applied migration V0001__one was modified");
+
+    migrations[0].up = "SELECT 1;".to_owned();
+    migrations.push(Migration::new(0, "zero", "SELECT 0;", None));
+    let err = Migrator::new(migrations).up(&mut driver, None).unwrap_err();
+    assert_eq!(err.to_string(), "pending migration V0000__zero is older than the last applied one");
+}
+
+#[test]
+fn test_migrations_write() {
+    let dir = "local-write-migrations";
+    let _ = std::fs::remove_dir_all(dir);
+    let path = ObjectPath::new_table("public", "users");
+    let users = Table::new(&path, vec![Field::new("id", &FieldAttributes::new_pk(FieldType::Int))], None);
+    let first = write_migration(dir, "create_users", vec![&users], &Postgresql{}).unwrap().expect("a migration");
+    assert_eq!(first.file_stem(), "V0001__create_users");
+    assert!(write_migration(dir, "nothing", vec![&users], &Postgresql{}).unwrap().is_none());
+
+    let index = Index::new(&path, &vec!["id".to_owned()]);
+    let second = write_migration(dir, "add_id_index", vec![&users, &index], &Postgresql{}).unwrap().expect("a migration");
+    assert_eq!(second.up, "CREATE INDEX users_id_idx ON public.users USING btree (id);\n");
+    assert_eq!(second.down.as_deref(), Some("DROP INDEX public.users_id_idx;\n"));
+    let migrations = read_migrations_dir(dir).unwrap();
+    assert_eq!(migrations, vec![first, second]);
+    assert_eq!(modified_files(dir).unwrap(), Vec::<String>::new());
+
+    std::fs::write(format!("{dir}/V0001__create_users.up.sql"), "DROP TABLE users;").unwrap();
+    assert_eq!(modified_files(dir).unwrap(), vec!["V0001__create_users.up.sql".to_owned()]);
+    let err = write_migration(dir, "more", vec![&users], &Postgresql{}).unwrap_err();
+    assert_eq!(err.to_string(), "generated migration files were modified: V0001__create_users.up.sql");
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,89 @@
+use dml_tools::sql::*;
+use dml_tools::type_writers::*;
+use dml_tools::Loader;
+use dml_tools::Processor;
+
+const MIXINS_YAML: &str = "
+- tag: Mixin
+  name: timestamps
+  fields:
+  - name: created_at
+    attributes: { empty: false, defval: now }
+  - name: updated_at
+    attributes: {}
+  indexes:
+  - [created_at]
+  triggers:
+  - name: '{table}_touch'
+    timing: Before
+    events: [Update]
+    function: { schema: demo, name: touch_updated_at, otype: Function }
+- tag: Mixin
+  name: tenancy
+  fields:
+  - name: workspace
+    attributes: { type: int, empty: false, index: true }
+  - name: updated_at
+    attributes: { type: int }
+- tag: Table
+  path: { schema: demo, name: projects }
+  mixins: [timestamps, tenancy]
+  fields:
+  - name: id
+    attributes: { type: int, primary_key: true, empty: false }
+  - name: created_at
+    attributes: { empty: false }
+";
+
+const MIXINS_SQL: &str = "CREATE TABLE demo.projects (
+  id int NOT NULL,
+  created_at text NOT NULL,
+  updated_at text NULL,
+  workspace int NOT NULL,
+  CONSTRAINT projects_id_pk PRIMARY KEY (id)
+);
+CREATE INDEX projects_created_at_idx ON demo.projects USING btree (created_at);
+CREATE TRIGGER projects_touch BEFORE UPDATE ON demo.projects
+FOR EACH ROW
+EXECUTE FUNCTION demo.touch_updated_at();";
+
+#[test]
+fn test_mixins_loader() {
+    let loader = Loader::new(MIXINS_YAML).expect("to load mixins");
+    let proc = Processor::new_with_objects(loader.objects(), None);
+    assert_eq!(proc.join_sql_statements(), MIXINS_SQL);
+    let table = loader.objects().iter().find_map(|o| o.downcast_ref::<Table>()).expect("a table");
+    assert!(table.mixins.is_empty());
+    assert_eq!(table.indexes().expect("indexes")[0].fields, vec!["workspace".to_owned()]);
+}
+
+#[test]
+fn test_mixins_unknown() {
+    let yaml = "
+- tag: Table
+  path: { schema: demo, name: projects }
+  mixins: [soft_delete]
+  fields:
+  - name: id
+    attributes: {}
+";
+    assert!(Loader::new(yaml).is_err());
+}
+
+#[test]
+fn test_mixin_apply() {
+    let mut mixin = Mixin::new("soft_delete", vec![Field::new("deleted_at", &FieldAttributes::new(FieldType::Txt))]);
+    let mut trigger = Trigger::new("{table}_soft_delete", &ObjectPath::default(), TriggerTiming::Before, vec![TriggerEvent::Delete]);
+    trigger.body = Some("UPDATE {table} SET deleted_at = CURRENT_TIMESTAMP WHERE id = OLD.id".to_owned());
+    mixin.triggers.push(trigger);
+    let mut table = Table::new(&ObjectPath::new_table_only("items"),
+        vec![Field::new("id", &FieldAttributes::new_pk(FieldType::Int))], None);
+    let objs = mixin.apply(&mut table);
+    assert_eq!(table.fields.len(), 2);
+    assert_eq!(objs.len(), 1);
+    assert_eq!(objs[0].to_sql(&Sqlite{}), "CREATE TRIGGER items_soft_delete BEFORE DELETE ON items
+FOR EACH ROW
+BEGIN
+UPDATE items SET deleted_at = CURRENT_TIMESTAMP WHERE id = OLD.id;
+END;");
+}
@@ -0,0 +1,122 @@
+use dml_tools::diff::Change;
+use dml_tools::planner::*;
+use dml_tools::sql::*;
+use dml_tools::type_writers::*;
+
+fn customers() -> Table {
+    Table::new(&ObjectPath::new_table("shop", "customers"), vec![
+        Field::new("id", &FieldAttributes::new_pk(FieldType::Int)),
+        Field::new("phone", &FieldAttributes::new(FieldType::Txt)),
+        Field::new("points", &FieldAttributes::new_nn(FieldType::Int)),
+    ], None)
+}
+
+#[test]
+fn test_rename_phases() {
+    let table = customers();
+    let mobile = Field::new("mobile", &FieldAttributes::new_nn(FieldType::Txt));
+    let plan = ExpandContract::rename(&table, "phone", &mobile).unwrap();
+    let phases = plan.phases(&Postgresql{}).unwrap();
+    let names:Vec<&str> = phases.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, vec!["expand", "backfill", "switch", "contract"]);
+
+    assert_eq!(phases[0].up[0], "ALTER TABLE shop.customers ADD COLUMN mobile text NULL;");
+    assert!(phases[0].up[1].contains("NEW.mobile := NEW.phone;"));
+    assert!(phases[0].up[2].starts_with("CREATE TRIGGER customers_mobile_sync BEFORE INSERT OR UPDATE OF phone ON shop.customers"));
+    assert_eq!(phases[0].down, vec![
+        "DROP TRIGGER customers_mobile_sync ON shop.customers;",
+        "DROP FUNCTION shop.customers_mobile_sync_fn;",
+        "ALTER TABLE shop.customers DROP COLUMN mobile;",
+    ]);
+    assert_eq!(phases[1].up, vec!["UPDATE shop.customers SET mobile = phone WHERE mobile IS NULL;"]);
+    assert!(phases[1].down.is_empty());
+    assert_eq!(phases[2].up, vec![
+        "DROP TRIGGER customers_mobile_sync ON shop.customers;",
+        "DROP FUNCTION shop.customers_mobile_sync_fn;",
+        "ALTER TABLE shop.customers ALTER COLUMN mobile SET NOT NULL;",
+    ]);
+    assert_eq!(phases[2].down[0], "ALTER TABLE shop.customers ALTER COLUMN mobile DROP NOT NULL;");
+    assert_eq!(phases[2].down.len(), 3);
+    assert_eq!(phases[3].up, vec!["ALTER TABLE shop.customers DROP COLUMN phone;"]);
+    assert_eq!(phases[3].down, vec![
+        "ALTER TABLE shop.customers ADD COLUMN phone text NULL;",
+        "UPDATE shop.customers SET phone = mobile WHERE phone IS NULL;",
+    ]);
+
+    assert!(ExpandContract::rename(&table, "fax", &mobile).is_err());
+    assert!(ExpandContract::rename(&table, "phone", &Field::new("points", &FieldAttributes::new(FieldType::Int))).is_err());
+}
+
+#[test]
+fn test_type_change_swaps_fields() {
+    let table = customers();
+    let points = Field::new("points", &FieldAttributes::new_nn(FieldType::BigInt));
+    let plan = ExpandContract::change(&table, &points).unwrap();
+    let phases = plan.phases(&Mysql{}).unwrap();
+    assert_eq!(phases[0].up[0], "ALTER TABLE shop.customers ADD COLUMN points_new bigint NULL;");
+    assert!(phases[0].up[1].contains("CREATE TRIGGER shop.customers_points_new_sync_insert BEFORE INSERT ON shop.customers"));
+    assert!(phases[0].up[1].contains("SET NEW.points_new = NEW.points;"));
+    assert_eq!(&phases[2].up[1..], &[
+        "ALTER TABLE shop.customers RENAME COLUMN points TO points_old;".to_owned(),
+        "ALTER TABLE shop.customers RENAME COLUMN points_new TO points;".to_owned(),
+        "ALTER TABLE shop.customers MODIFY COLUMN points bigint NOT NULL;".to_owned(),
+    ]);
+    assert_eq!(&phases[2].down[..3], &[
+        "ALTER TABLE shop.customers MODIFY COLUMN points bigint NULL;".to_owned(),
+        "ALTER TABLE shop.customers RENAME COLUMN points TO points_new;".to_owned(),
+        "ALTER TABLE shop.customers RENAME COLUMN points_old TO points;".to_owned(),
+    ]);
+    assert_eq!(phases[3].up, vec!["ALTER TABLE shop.customers DROP COLUMN points_old;"]);
+    assert_eq!(phases[3].down[0], "ALTER TABLE shop.customers ADD COLUMN points_old int NULL;");
+
+    let change = Change::AlterField { table: &table, old: &table.fields[2], new: &points };
+    let from_diff = ExpandContract::from_change(&change).unwrap();
+    assert_eq!(from_diff.phases(&Mysql{}).unwrap(), phases);
+    assert!(ExpandContract::from_change(&Change::CreateTable(&table)).is_none());
+}
+
+#[test]
+fn test_sqlite_sync_triggers_and_migrations() {
+    let table = customers();
+    let mobile = Field::new("mobile", &FieldAttributes::new(FieldType::Txt));
+    let plan = ExpandContract::rename(&table, "phone", &mobile).unwrap();
+    let expand = &plan.phases(&Sqlite{}).unwrap()[0];
+    assert!(expand.up[1].starts_with("CREATE TRIGGER customers_mobile_sync_insert AFTER INSERT ON customers"));
+    assert!(expand.up[1].contains("UPDATE customers SET mobile = NEW.phone WHERE id = NEW.id;"));
+
+    let migrations = plan.migrations(7, "phone_to_mobile", &Sqlite{}).unwrap();
+    let stems:Vec<String> = migrations.iter().map(|m| m.file_stem()).collect();
+    assert_eq!(stems, vec![
+        "V0007__phone_to_mobile_expand",
+        "V0008__phone_to_mobile_backfill",
+        "V0009__phone_to_mobile_switch",
+        "V0010__phone_to_mobile_contract",
+    ]);
+    // a nullable target needs no NOT NULL in the switch phase
+    assert_eq!(migrations[2].up, "DROP TRIGGER customers_mobile_sync_insert;\nDROP TRIGGER customers_mobile_sync_update;\n");
+
+    // SQLite can not SET NOT NULL, the table is rebuilt instead
+    let mobile = Field::new("mobile", &FieldAttributes::new_nn(FieldType::Txt));
+    let plan = ExpandContract::rename(&table, "phone", &mobile).unwrap();
+    let switch = &plan.phases(&Sqlite{}).unwrap()[2];
+    assert!(switch.up[1].contains("CREATE TABLE new_customers (\n  id integer NOT NULL,\n  phone text NULL,\n  points integer NOT NULL,\n  mobile text NOT NULL,"), "{}", switch.up[1]);
+    assert!(switch.up[1].contains("INSERT INTO new_customers (id, phone, points, mobile) SELECT id, phone, points, mobile FROM customers;"));
+    assert!(switch.down[0].contains("  mobile text NULL,"));
+}
+
+#[test]
+fn test_narrowing_type_change_casts() {
+    let table = customers();
+    let phone = Field::new("phone", &FieldAttributes::new(FieldType::Int));
+    let plan = ExpandContract::change(&table, &phone).unwrap();
+    let phases = plan.phases(&Postgresql{}).unwrap();
+    assert!(phases[0].up[1].contains("NEW.phone_new := CAST(NEW.phone AS int);"), "{}", phases[0].up[1]);
+    assert_eq!(phases[1].up, vec!["UPDATE shop.customers SET phone_new = CAST(phone AS int) WHERE phone_new IS NULL;"]);
+    assert_eq!(phases[3].down[1], "UPDATE shop.customers SET phone_old = CAST(phone AS text) WHERE phone_old IS NULL;");
+
+    let phases = plan.phases(&Sqlite{}).unwrap();
+    assert!(phases[0].up[1].contains("UPDATE customers SET phone_new = CAST(NEW.phone AS integer) WHERE id = NEW.id;"), "{}", phases[0].up[1]);
+    // MySQL converts on assignment
+    let phases = plan.phases(&Mysql{}).unwrap();
+    assert_eq!(phases[1].up, vec!["UPDATE shop.customers SET phone_new = phone WHERE phone_new IS NULL;"]);
+}
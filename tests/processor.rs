@@ -2,6 +2,7 @@ use std::fs::remove_file;
 
 use dml_tools::Loader;
 use dml_tools::Processor;
+use dml_tools::ProcessorOptions;
 use dml_tools::type_writers::*;
 use dml_tools::sql::*;
 use serde::{Deserialize, Serialize};
@@ -165,6 +166,55 @@ fn test_processor_sqlite_from_yaml() {
     assert_eq!(generated, expected)
 }
 
+const OPTIONS_YAML: &str = "
+- tag: Schema
+  name: shop
+  owner: shop_owner
+- tag: Table
+  path: { schema: shop, name: items }
+  fields:
+  - name: id
+    attributes: { type: int, primary_key: true, empty: false }
+  - name: sku
+    attributes: {}
+  fks: ~
+- tag: Index
+  table: { schema: shop, name: items }
+  fields: [ sku ]
+- tag: Grant
+  permission: Select
+  to: reports
+  on: { schema: shop, name: items, otype: Table }
+";
+
+#[test]
+fn test_processor_options() {
+    let loader = Loader::new(OPTIONS_YAML).unwrap();
+    let mut proc = Processor::new_with_objects(loader.objects(), None);
+    proc.set_options(ProcessorOptions { transaction: true, idempotent: true });
+    let statements = proc.sql_statements();
+    assert_eq!(statements.first().unwrap(), "BEGIN;");
+    assert_eq!(statements[1], "CREATE SCHEMA IF NOT EXISTS shop AUTHORIZATION shop_owner;");
+    assert!(statements[2].starts_with("CREATE TABLE IF NOT EXISTS shop.items (\n"));
+    assert_eq!(statements[3], "CREATE INDEX IF NOT EXISTS items_sku_idx ON shop.items USING btree (sku);");
+    assert_eq!(statements[4], "GRANT SELECT ON TABLE shop.items TO reports;");
+    assert_eq!(statements.last().unwrap(), "COMMIT;");
+
+    // no transactional DDL nor CREATE INDEX IF NOT EXISTS in MySQL
+    let mut proc = Processor::new_with_objects(loader.objects(), Some(Box::new(Mysql{})));
+    proc.set_options(ProcessorOptions { transaction: true, idempotent: true });
+    let statements = proc.sql_statements();
+    assert!(statements[1].starts_with("CREATE TABLE IF NOT EXISTS shop.items (\n"));
+    assert_eq!(statements[2], "CREATE INDEX items_sku_idx ON shop.items USING btree (sku);");
+    assert!(! statements.iter().any(|s| s == "BEGIN;" || s == "COMMIT;"));
+
+    let mut proc = Processor::new_with_objects(loader.objects(), Some(Box::new(Sqlite{})));
+    proc.set_options(ProcessorOptions { transaction: true, ..Default::default() });
+    let statements = proc.sql_statements();
+    assert_eq!(statements[0], "BEGIN;");
+    assert!(statements[1].starts_with("CREATE TABLE items (\n"));
+}
+
 // // const DEL_FILE : &str = "tests/fixtures/delayed.yaml";
 // #[test]
 // fn test_processor_delayed() {
@@ -175,4 +225,38 @@ fn test_processor_sqlite_from_yaml() {
 //     // let tr: BxTypeWriter = Box::new(Postgresql{});
 //     let proc = Processor::new_with_objects(loader.objects(),Some(tr));
 //     proc.write_to_sql_file("local-delayed.sql").expect("to write delayed file");
-// }
\ No newline at end of file
+// }
+#[test]
+fn test_processor_idempotent_table_comments() {
+    let mut table = Table::new(&ObjectPath::new_table("shop", "stuff"), vec![
+        Field::new("id", &FieldAttributes::new_pk(FieldType::Int)),
+    ], None);
+    table.comment = Some("created by CREATE TABLE stuff".to_owned());
+    let options = ProcessorOptions { idempotent: true, ..Default::default() };
+
+    let mut proc = Processor::new(None);
+    proc.add(&table);
+    proc.set_options(options.clone());
+    assert_eq!(proc.sql_statements(), vec!["CREATE TABLE IF NOT EXISTS shop.stuff (
+  id int NOT NULL,
+  CONSTRAINT stuff_id_pk PRIMARY KEY (id)
+);
+COMMENT ON TABLE shop.stuff IS 'created by CREATE TABLE stuff';"]);
+
+    let mut proc = Processor::new(Some(Box::new(Mysql{})));
+    proc.add(&table);
+    proc.set_options(options.clone());
+    assert_eq!(proc.sql_statements(), vec!["CREATE TABLE IF NOT EXISTS shop.stuff (
+  id int NOT NULL,
+  CONSTRAINT stuff_id_pk PRIMARY KEY (id)
+) COMMENT='created by CREATE TABLE stuff';"]);
+
+    let mut proc = Processor::new(Some(Box::new(Sqlite{})));
+    proc.add(&table);
+    proc.set_options(options);
+    assert_eq!(proc.sql_statements(), vec!["-- created by CREATE TABLE stuff
+CREATE TABLE IF NOT EXISTS stuff (
+  id integer NOT NULL,
+  CONSTRAINT stuff_id_pk PRIMARY KEY (id)
+);"]);
+}
@@ -0,0 +1,127 @@
+use dml_tools::diff::*;
+use dml_tools::migrations::write_checked_migration;
+use dml_tools::safety::*;
+use dml_tools::sql::*;
+use dml_tools::type_writers::*;
+use dml_tools::Loader;
+
+const OLD_YAML: &str = "
+- tag: Table
+  path: { schema: shop, name: customers }
+  fields:
+  - name: id
+    attributes: { type: int, primary_key: true, empty: false }
+  - name: code
+    attributes: { unique: true }
+  - name: phone
+    attributes: {}
+  fks: ~
+- tag: Table
+  path: { schema: shop, name: orders }
+  fields:
+  - name: id
+    attributes: { type: int, primary_key: true, empty: false }
+  - name: customer
+    attributes: { type: int }
+  - name: total
+    attributes: { type: double }
+  - name: items
+    attributes: { type: int }
+  fks: ~
+- tag: Sequence
+  path: { schema: shop, name: tickets, otype: Sequence }
+";
+
+const NEW_YAML: &str = "
+- tag: Table
+  path: { schema: shop, name: customers }
+  fields:
+  - name: id
+    attributes: { type: int, primary_key: true, empty: false }
+  - name: code
+    attributes: {}
+  fks: ~
+- tag: Table
+  path: { schema: shop, name: orders }
+  fields:
+  - name: id
+    attributes: { type: int, primary_key: true, empty: false }
+  - name: customer
+    attributes: { type: int, empty: false, index: true }
+  - name: total
+    attributes: { type: int }
+  - name: items
+    attributes: { type: bigint }
+  fks:
+  - table: { schema: shop, name: orders }
+    fields: [customer]
+    ref_table: { schema: shop, name: customers }
+    ref_fields: [id]
+";
+
+#[test]
+fn test_safety_analyze() {
+    let old = Loader::new(OLD_YAML).unwrap();
+    let new = Loader::new(NEW_YAML).unwrap();
+    let diff = SchemaDiff::from_loaders(&old, &new);
+    let findings:Vec<(String, Risk)> = analyze(&diff).into_iter().map(|f| (f.change, f.risk)).collect();
+    assert_eq!(findings, vec![
+        ("drop Sequence shop.tickets".to_owned(), Risk::DataLosing),
+        ("drop constraint customers_code_uk".to_owned(), Risk::DataLosing),
+        ("drop field shop.customers.phone".to_owned(), Risk::DataLosing),
+        ("alter field shop.orders.customer".to_owned(), Risk::LockHeavy),
+        ("alter field shop.orders.total".to_owned(), Risk::DataLosing),
+        ("alter field shop.orders.items".to_owned(), Risk::LockHeavy),
+        ("create index orders_customer_idx".to_owned(), Risk::LockHeavy),
+        ("add constraint orders_customers_customer_fk".to_owned(), Risk::LockHeavy),
+    ]);
+    let total = &analyze(&diff)[4];
+    assert_eq!(total.reason.as_deref(), Some("narrows the type from Dbl to Int"));
+    assert!(narrows(&FieldType::BigInt, &FieldType::Int));
+    assert!(! narrows(&FieldType::Int, &FieldType::Txt));
+}
+
+#[test]
+fn test_safety_strict_and_not_valid() {
+    let old = Loader::new(OLD_YAML).unwrap();
+    let new = Loader::new(&NEW_YAML.replace("    ref_fields: [id]", "    ref_fields: [id]\n    not_valid: true")).unwrap();
+    let diff = SchemaDiff::from_loaders(&old, &new);
+    let findings = analyze(&diff);
+    assert_eq!(findings.last().unwrap().risk, Risk::Safe);
+    let fk = new.objects()[1].downcast_ref::<Table>().unwrap().foreign_keys().remove(0);
+    assert_eq!(diff.up_sql(&Postgresql{}).unwrap().last().unwrap(), "ALTER TABLE shop.orders ADD CONSTRAINT orders_customers_customer_fk FOREIGN KEY (customer) REFERENCES shop.customers (id) ON DELETE RESTRICT ON UPDATE RESTRICT NOT VALID;");
+    assert_eq!(fk.validate_sql(&Postgresql{}), "ALTER TABLE shop.orders VALIDATE CONSTRAINT orders_customers_customer_fk;");
+    assert_eq!(diff.up_sql(&Mysql{}).unwrap().last().unwrap(), "ALTER TABLE shop.orders ADD CONSTRAINT orders_customers_customer_fk FOREIGN KEY (customer) REFERENCES shop.customers (id) ON DELETE RESTRICT ON UPDATE RESTRICT;");
+    assert_eq!(fk.validate_sql(&Mysql{}), "");
+
+    assert!(SafetyCheck::default().check(&diff).is_ok());
+    let err = SafetyCheck::new_strict().check(&diff).unwrap_err();
+    assert_eq!(err.to_string(), "data-losing changes not acknowledged: drop Sequence shop.tickets, drop constraint customers_code_uk, drop field shop.customers.phone, alter field shop.orders.total");
+    let check = SafetyCheck::new_strict()
+        .acknowledge("drop Sequence shop.tickets")
+        .acknowledge("drop constraint customers_code_uk")
+        .acknowledge("drop field shop.customers.phone");
+    assert!(check.check(&diff).is_err());
+    assert_eq!(check.acknowledge("alter field shop.orders.total").check(&diff).unwrap().len(), 8);
+}
+
+#[test]
+fn test_safety_write_migration() {
+    let dir = "local-safety-migrations";
+    let _ = std::fs::remove_dir_all(dir);
+    let path = ObjectPath::new_table("public", "users");
+    let users = Table::new(&path, vec![
+        Field::new("id", &FieldAttributes::new_pk(FieldType::Int)),
+        Field::new("name", &FieldAttributes::new(FieldType::Txt)),
+    ], None);
+    let strict = SafetyCheck::new_strict();
+    assert!(write_checked_migration(dir, "create_users", vec![&users], &Postgresql{}, &strict).unwrap().is_some());
+    let fewer = Table::new(&path, vec![Field::new("id", &FieldAttributes::new_pk(FieldType::Int))], None);
+    let err = write_checked_migration(dir, "drop_name", vec![&fewer], &Postgresql{}, &strict).unwrap_err();
+    assert_eq!(err.to_string(), "data-losing changes not acknowledged: drop field public.users.name");
+    assert_eq!(std::fs::read_dir(dir).unwrap().count(), 4);
+    let strict = strict.acknowledge("drop field public.users.name");
+    let migration = write_checked_migration(dir, "drop_name", vec![&fewer], &Postgresql{}, &strict).unwrap().unwrap();
+    assert_eq!(migration.up, "ALTER TABLE public.users DROP COLUMN name;\n");
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,47 @@
+use dml_tools::sql::*;
+use dml_tools::type_writers::*;
+use dml_tools::Processor;
+
+type BxTypeWriter = Box<dyn TypeWriter>;
+
+fn sequence_sqls(type_writer:BxTypeWriter) -> String {
+    let opath = ObjectPath::new_sequence("demo", "orders_number_seq");
+    let mut seq = Sequence::new(&opath);
+    seq.start = Some(1000);
+    seq.increment = Some(10);
+    seq.min_value = Some(1000);
+    seq.cache = Some(5);
+    seq.cycle = true;
+    let fields = vec![
+        Field::new("number", &FieldAttributes::new_nn_seq(FieldType::BigInt, &opath)),
+        Field::new("name", &FieldAttributes::new(FieldType::Txt)),
+    ];
+    let table = Table::new(&ObjectPath::new_table("demo", "orders"), fields, None);
+    let mut owned = Sequence::new(&ObjectPath::new_sequence("demo", "orders_other_seq"));
+    owned.owned_by = Some(SequenceOwner{ table: table.path.to_owned(), field: "name".to_owned() });
+
+    let mut proc = Processor::new(Some(type_writer));
+    proc.add(&table);
+    proc.add(&owned);
+    proc.add(&seq);
+    proc.join_sql_statements()
+}
+
+#[test]
+fn test_sequence_pgsql() {
+    assert_eq!(sequence_sqls(Box::new(Postgresql{})), "CREATE SEQUENCE demo.orders_other_seq;
+CREATE SEQUENCE demo.orders_number_seq INCREMENT BY 10 MINVALUE 1000 START WITH 1000 CACHE 5 CYCLE;
+CREATE TABLE demo.orders (
+  number bigint NOT NULL DEFAULT nextval('demo.orders_number_seq'),
+  name text NULL
+);
+ALTER SEQUENCE demo.orders_other_seq OWNED BY demo.orders.name;");
+}
+
+#[test]
+fn test_sequence_skipped_without_support() {
+    assert_eq!(sequence_sqls(Box::new(Sqlite{})), "CREATE TABLE orders (
+  number integer NOT NULL,
+  name text NULL
+);");
+}
@@ -0,0 +1,214 @@
+#![cfg(feature = "sqlite")]
+use dml_tools::diff::SchemaDiff;
+use dml_tools::sql::*;
+use dml_tools::migrations::*;
+use dml_tools::planner::ExpandContract;
+use dml_tools::sqlite::{read_sqlite, verify_sql, SqliteDriver};
+use dml_tools::type_writers::*;
+use dml_tools::util::read_file_into_string;
+use dml_tools::Loader;
+use dml_tools::Processor;
+use rusqlite::Connection;
+
+#[test]
+fn test_sqlite_round_trip() {
+    let sql = read_file_into_string("tests/fixtures/proc_sqlite.sql");
+    let file_name = "local-introspect.db";
+    let _ = std::fs::remove_file(file_name);
+    Connection::open(file_name).expect("to create db").execute_batch(&sql).expect("to run SQL");
+    let loader = Loader::new_from_sqlite_file(file_name).expect("to introspect db");
+    std::fs::remove_file(file_name).unwrap();
+    assert_eq!(loader.warnings(), &Vec::<String>::new());
+    let proc = Processor::new_with_objects(loader.objects(), Some(Box::new(Sqlite{})));
+    assert_eq!(proc.join_sql_statements(), sql);
+}
+
+#[test]
+fn test_sqlite_warnings() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch("
+CREATE TABLE parent (id integer PRIMARY KEY, code text, name text, UNIQUE (code), UNIQUE (name));
+CREATE TABLE child (
+  id integer PRIMARY KEY,
+  parent_id integer REFERENCES parent ON DELETE SET NULL,
+  stamp datetime DEFAULT 'now'
+);
+CREATE UNIQUE INDEX child_stamp ON child (stamp);
+CREATE TABLE c (pid integer NOT NULL REFERENCES parent(id) ON DELETE SET NULL);
+CREATE VIEW v AS SELECT 1;
+").unwrap();
+    let (objs, warnings) = read_sqlite(&conn).expect("to introspect db");
+    assert_eq!(warnings, vec![
+        "UNIQUE constraints of parent merged into one".to_owned(),
+        "type 'datetime' of child.stamp read as Txt".to_owned(),
+        "unique index child_stamp read as the non unique index child_stamp_idx".to_owned(),
+        "reference from child to the primary key of parent skipped".to_owned(),
+        "c_parent_pid_fk uses SET NULL on NOT NULL fields, skipped".to_owned(),
+        "view v not read".to_owned(),
+    ]);
+    assert_eq!(objs.len(), 4);
+    let parent = objs[0].downcast_ref::<Table>().expect("a table");
+    assert!(parent.fields[1].attributes.unique && parent.fields[2].attributes.unique);
+    let child = objs[1].downcast_ref::<Table>().expect("a table");
+    assert_eq!(child.fields[2].attributes.defval.as_deref(), Some("now"));
+    assert!(child.fields[0].attributes.primary_key);
+    assert!(child.foreign_keys().is_empty());
+    let c = objs[3].downcast_ref::<Table>().expect("a table");
+    assert!(c.foreign_keys().is_empty());
+}
+
+#[test]
+fn test_sqlite_verify_sql() {
+    let loader = Loader::new_from_sql_file("tests/fixtures/proc_pgsql.sql").expect("to read SQL");
+    let mut proc = Processor::new_with_objects(loader.objects(), None);
+    assert_eq!(verify_sql(&proc).expect("to run SQL"), vec![]);
+    let view = View::new(&ObjectPath::new_view("public", "broken"), "SELEC 1");
+    proc.add(&view);
+    let failed = verify_sql(&proc).expect("to run SQL");
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].object, "View public.broken");
+    assert!(failed[0].sql.contains("SELEC 1"));
+    assert!(failed[0].error.contains("syntax error"), "{}", failed[0]);
+}
+
+#[test]
+fn test_sqlite_migrations() {
+    let migrator = Migrator::from_dir("tests/fixtures/migrations").unwrap();
+    let mut driver = SqliteDriver::new(Connection::open_in_memory().unwrap());
+    assert_eq!(migrator.up(&mut driver, None).unwrap(), vec![1, 2, 3]);
+    let applied = driver.applied().unwrap();
+    assert_eq!(applied.len(), 3);
+    assert_eq!(applied[2].checksum, migrator.migrations()[2].checksum());
+    driver.connection().execute_batch("DROP INDEX users_email_idx; DELETE FROM _dml_tools_migrations WHERE version = 3").unwrap();
+    assert_eq!(migrator.down(&mut driver, 1).unwrap(), vec![2]);
+    let (objs, _) = read_sqlite(driver.connection()).unwrap();
+    let users = objs[0].downcast_ref::<Table>().expect("users table");
+    assert_eq!(users.fields.len(), 2);
+
+    let broken = Migrator::new(vec![Migration::new(2, "broken", "ALTER TABLE users ADD COLUMN x text; SELEC 1;", None)]);
+    assert!(broken.up(&mut driver, None).is_err());
+    let (objs, _) = read_sqlite(driver.connection()).unwrap();
+    assert_eq!(objs[0].downcast_ref::<Table>().unwrap().fields.len(), 2);
+    assert_eq!(driver.applied().unwrap().len(), 1);
+}
+
+#[test]
+fn test_sqlite_rebuild_migration() {
+    let old = Loader::new("
+- tag: Table
+  path: { name: items }
+  fields:
+  - name: id
+    attributes: { type: int, primary_key: true, empty: false }
+  - name: qty
+    attributes: { type: int }
+  - name: note
+    attributes: {}
+  fks: ~
+").unwrap();
+    let new = Loader::new("
+- tag: Table
+  path: { name: items }
+  fields:
+  - name: id
+    attributes: { type: int, primary_key: true, empty: false }
+  - name: qty
+    attributes: { type: int, empty: false, defval: '1', index: true }
+  fks: ~
+").unwrap();
+    let diff = SchemaDiff::from_loaders(&old, &new);
+    let migration = Migration::new(1, "qty_not_null", &diff.up_sql(&Sqlite{}).unwrap().join("\n"), Some(&diff.down_sql(&Sqlite{}).unwrap().join("\n")));
+    let mut driver = SqliteDriver::new(Connection::open_in_memory().unwrap());
+    driver.init().unwrap();
+    driver.connection().execute_batch("CREATE TABLE items (id integer NOT NULL PRIMARY KEY, qty integer NULL, note text NULL);
+INSERT INTO items VALUES (1, 5, 'five');
+INSERT INTO items VALUES (2, NULL, 'none');").unwrap();
+    assert!(migration.up.contains("INSERT INTO new_items (id, qty) SELECT id, COALESCE(qty, 1) FROM items;"));
+    driver.apply(&migration).unwrap();
+    let (objs, _) = read_sqlite(driver.connection()).unwrap();
+    let items = objs[0].downcast_ref::<Table>().expect("items table");
+    assert_eq!(items.fields.len(), 2);
+    assert!(! items.fields[1].attributes.empty);
+    let indexes:i64 = driver.connection().query_row("SELECT count(*) FROM sqlite_master WHERE type = 'index' AND name = 'items_qty_idx'", [], |r| r.get(0)).unwrap();
+    assert_eq!(indexes, 1);
+    let qty:i64 = driver.connection().query_row("SELECT qty FROM items WHERE id = 1", [], |r| r.get(0)).unwrap();
+    assert_eq!(qty, 5);
+    let qty:i64 = driver.connection().query_row("SELECT qty FROM items WHERE id = 2", [], |r| r.get(0)).unwrap();
+    assert_eq!(qty, 1);
+    assert_eq!(driver.applied().unwrap().len(), 1);
+
+    driver.revert(&migration).unwrap();
+    let (objs, _) = read_sqlite(driver.connection()).unwrap();
+    assert_eq!(objs[0].downcast_ref::<Table>().unwrap().fields.len(), 3);
+    assert!(driver.applied().unwrap().is_empty());
+}
+
+#[test]
+fn test_sqlite_migration_foreign_key_check() {
+    let mut driver = SqliteDriver::new(Connection::open_in_memory().unwrap());
+    driver.init().unwrap();
+    driver.connection().execute_batch("PRAGMA foreign_keys=on;
+CREATE TABLE parent (id integer PRIMARY KEY);
+CREATE TABLE child (id integer PRIMARY KEY, parent_id integer REFERENCES parent (id));
+CREATE TABLE other (id integer PRIMARY KEY, parent_id integer REFERENCES parent (id));").unwrap();
+    let orphan = Migration::new(1, "orphan", "INSERT INTO child VALUES (1, 42);", None);
+    let err = driver.apply(&orphan).expect_err("a foreign key violation");
+    assert_eq!(err.to_string(), "FOREIGN KEY constraint failed");
+
+    // rebuilds run with foreign keys off, checking only the rebuilt tables
+    let rebuild = Migration::new(1, "rebuild", "PRAGMA foreign_keys=off;
+BEGIN;
+CREATE TABLE new_child (id integer PRIMARY KEY, parent_id integer NOT NULL REFERENCES parent (id));
+INSERT INTO new_child (id, parent_id) SELECT id, parent_id FROM child;
+INSERT INTO new_child VALUES (2, 42);
+DROP TABLE child;
+ALTER TABLE new_child RENAME TO child;
+PRAGMA foreign_key_check;
+COMMIT;
+PRAGMA foreign_keys=on;", None);
+    let err = driver.apply(&rebuild).expect_err("a foreign key violation");
+    assert_eq!(err.to_string(), "foreign keys would be violated, rolled back: child row 2 references a missing parent row");
+    assert!(driver.applied().unwrap().is_empty());
+    let children:i64 = driver.connection().query_row("SELECT count(*) FROM child", [], |r| r.get(0)).unwrap();
+    assert_eq!(children, 0);
+    let foreign_keys:bool = driver.connection().query_row("PRAGMA foreign_keys", [], |r| r.get(0)).unwrap();
+    assert!(foreign_keys);
+
+    driver.connection().execute_batch("PRAGMA foreign_keys=off; INSERT INTO other VALUES (1, 42); PRAGMA foreign_keys=on;").unwrap();
+    let fixed = Migration::new(1, "rebuild", &rebuild.up.replace("INSERT INTO new_child VALUES (2, 42);\n", ""), None);
+    driver.apply(&fixed).unwrap();
+    assert_eq!(driver.applied().unwrap().len(), 1);
+}
+
+#[test]
+fn test_sqlite_expand_contract_plan() {
+    let table = Table::new(&ObjectPath::new_table_only("customers"), vec![
+        Field::new("id", &FieldAttributes::new_pk(FieldType::Int)),
+        Field::new("phone", &FieldAttributes::new(FieldType::Txt)),
+    ], None);
+    let mobile = Field::new("mobile", &FieldAttributes::new_nn(FieldType::Txt));
+    let plan = ExpandContract::rename(&table, "phone", &mobile).unwrap();
+    let migrator = Migrator::new(plan.migrations(1, "phone_to_mobile", &Sqlite{}).unwrap());
+    let mut driver = SqliteDriver::new(Connection::open_in_memory().unwrap());
+    driver.init().unwrap();
+    driver.connection().execute_batch(&(table.to_sql(&Sqlite{}) + "
+INSERT INTO customers VALUES (1, '555');")).unwrap();
+    let not_null = |driver:&SqliteDriver| -> i64 {
+        driver.connection().query_row("SELECT \"notnull\" FROM pragma_table_info('customers') WHERE name = 'mobile'", [], |r| r.get(0)).unwrap()
+    };
+
+    assert_eq!(migrator.up(&mut driver, Some(2)).unwrap(), vec![1, 2]);
+    driver.connection().execute_batch("INSERT INTO customers VALUES (2, '556', NULL);").unwrap();
+    let mobile:String = driver.connection().query_row("SELECT mobile FROM customers WHERE id = 2", [], |r| r.get(0)).unwrap();
+    assert_eq!(mobile, "556");
+    assert_eq!(not_null(&driver), 0);
+
+    assert_eq!(migrator.up(&mut driver, None).unwrap(), vec![3, 4]);
+    assert_eq!(not_null(&driver), 1);
+    let mobiles:Vec<String> = driver.connection().prepare("SELECT mobile FROM customers ORDER BY id").unwrap()
+        .query_map([], |r| r.get(0)).unwrap().map(|m| m.unwrap()).collect();
+    assert_eq!(mobiles, vec!["555", "556"]);
+
+    assert_eq!(migrator.down(&mut driver, 2).unwrap(), vec![4, 3]);
+    assert_eq!(not_null(&driver), 0);
+}
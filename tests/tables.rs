@@ -18,8 +18,9 @@ fn test_tables() {
         ref_table:ObjectPath::new_table("demo", "cache"),
         ref_fields:vec!["ws".to_owned(), "user".to_owned()],
         on_delete:FKOn::Restrict, on_update:FKOn::Restrict,
+        deferrable:false, initially_deferred:false, match_type:None, not_valid:false,
     };
-    let tbl = Table::new(&&table_path, fields, Some(vec![fk]));
+    let tbl = Table::new(&table_path, fields, Some(vec![fk]));
     // println!("\n{}", tbl.to_sql());
     // dml_tools::util::write_yaml_to_file("local-table.yaml", &tbl).expect("To table to file");
     let ttf="tests/fixtures/test-table.sql";
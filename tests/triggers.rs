@@ -0,0 +1,103 @@
+use dml_tools::sql::*;
+use dml_tools::type_writers::*;
+use dml_tools::Loader;
+
+fn audit_trigger() -> Trigger {
+    let mut trigger = Trigger::new("users_audit", &ObjectPath::new_table("my_schema", "users"), TriggerTiming::After,
+        vec![TriggerEvent::Insert, TriggerEvent::UpdateOf(vec!["role".to_owned(), "email".to_owned()])]);
+    trigger.when = Some("NEW.is_locked = false".to_owned());
+    trigger.function = Some(ObjectPath::new_function("my_schema", "users_audit_fn"));
+    trigger.body = Some("INSERT INTO my_schema.audit (user_id) VALUES (NEW.user_id)".to_owned());
+    trigger
+}
+
+#[test]
+fn test_trigger_pgsql() {
+    assert_eq!(audit_trigger().to_sql(&Postgresql{}), "CREATE TRIGGER users_audit AFTER INSERT OR UPDATE OF role,email ON my_schema.users
+FOR EACH ROW
+WHEN (NEW.is_locked = false)
+EXECUTE FUNCTION my_schema.users_audit_fn();");
+}
+
+#[test]
+fn test_trigger_mysql() {
+    assert_eq!(audit_trigger().to_sql(&Mysql{}), "DELIMITER $$
+CREATE TRIGGER my_schema.users_audit_insert AFTER INSERT ON my_schema.users
+FOR EACH ROW
+BEGIN
+IF NEW.is_locked = false THEN
+INSERT INTO my_schema.audit (user_id) VALUES (NEW.user_id);
+END IF;
+END$$
+CREATE TRIGGER my_schema.users_audit_update AFTER UPDATE ON my_schema.users
+FOR EACH ROW
+BEGIN
+IF (NEW.is_locked = false) AND NOT (NEW.role <=> OLD.role AND NEW.email <=> OLD.email) THEN
+INSERT INTO my_schema.audit (user_id) VALUES (NEW.user_id);
+END IF;
+END$$
+DELIMITER ;");
+}
+
+#[test]
+fn test_trigger_sqlite() {
+    assert_eq!(audit_trigger().to_sql(&Sqlite{}), "CREATE TRIGGER users_audit_insert AFTER INSERT ON users
+FOR EACH ROW
+WHEN NEW.is_locked = false
+BEGIN
+INSERT INTO my_schema.audit (user_id) VALUES (NEW.user_id);
+END;
+CREATE TRIGGER users_audit_update AFTER UPDATE OF role,email ON users
+FOR EACH ROW
+WHEN NEW.is_locked = false
+BEGIN
+INSERT INTO my_schema.audit (user_id) VALUES (NEW.user_id);
+END;");
+}
+
+#[test]
+fn test_trigger_unsupported() {
+    let mut trigger = audit_trigger();
+    trigger.for_each = TriggerScope::Statement;
+    assert_eq!(trigger.to_sql(&Sqlite{}), "");
+    assert_eq!(trigger.to_sql(&Mysql{}), "");
+    trigger.function = None;
+    assert_eq!(trigger.to_sql(&Postgresql{}), "");
+}
+
+#[test]
+fn test_trigger_loader_checks() {
+    let err = Loader::new("
+- tag: Trigger
+  name: users_touch
+  table: { schema: my_schema, name: users }
+  timing: Before
+  events: [Update]
+").err().expect("a trigger without function nor body");
+    assert_eq!(err.to_string(), "trigger users_touch on my_schema.users has neither a function nor a body");
+
+    let loader = Loader::new("
+- tag: Trigger
+  name: users_touch
+  table: { schema: my_schema, name: users }
+  timing: Before
+  events: [Update]
+  body: SET NEW.updated_at = now()
+- tag: Trigger
+  name: users_audit
+  table: { schema: my_schema, name: users }
+  timing: After
+  events: [Insert]
+  function: { schema: my_schema, name: users_audit_fn, otype: Function }
+").expect("to load triggers");
+    assert_eq!(loader.warnings(), &vec![
+        "trigger users_touch on my_schema.users has no function, skipped by Postgresql".to_owned(),
+        "trigger users_audit on my_schema.users has no body, skipped by Mysql and Sqlite".to_owned(),
+    ]);
+}
+
+#[test]
+fn test_loader_invalid_yaml() {
+    assert!(Loader::new("- tag: Nope").is_err());
+    assert!(Loader::new_from_file("tests/missing.yaml").is_err());
+}
@@ -0,0 +1,132 @@
+use dml_tools::sql::*;
+use dml_tools::type_writers::*;
+use dml_tools::Loader;
+use dml_tools::Processor;
+
+const VIEWS_YAML: &str = "
+- tag: View
+  path: { schema: reports, name: active_users, otype: View }
+  columns: [id, name]
+  query: SELECT id, user_name FROM reports.users WHERE NOT is_locked
+  check_option: true
+  depends_on:
+  - { schema: reports, name: users }
+- tag: Grant
+  permission: Select
+  to: ro_user
+  on: { schema: reports, name: active_users, otype: View }
+- tag: MaterializedView
+  path: { schema: reports, name: users_by_role, otype: MaterializedView }
+  query: SELECT role, count(*) AS total FROM reports.users GROUP BY role;
+  unique_index: [role]
+  depends_on:
+  - { schema: reports, name: users }
+- tag: Owner
+  to: rw_user
+  of: { schema: reports, name: users_by_role, otype: MaterializedView }
+- tag: Table
+  path: { schema: reports, name: users }
+  fields:
+  - name: id
+    attributes: { type: int, primary_key: true, empty: false }
+  - name: user_name
+    attributes: {}
+  - name: role
+    attributes: {}
+  - name: is_locked
+    attributes: { type: bool }
+";
+
+#[test]
+fn test_views_pgsql() {
+    let loader = Loader::new(VIEWS_YAML).expect("to load views");
+    let proc = Processor::new_with_objects(loader.objects(), None);
+    assert_eq!(proc.join_sql_statements(), "CREATE TABLE reports.users (
+  id int NOT NULL,
+  user_name text NULL,
+  \"role\" text NULL,
+  is_locked bool NULL,
+  CONSTRAINT users_id_pk PRIMARY KEY (id)
+);
+CREATE VIEW reports.active_users (id,name) AS
+SELECT id, user_name FROM reports.users WHERE NOT is_locked
+WITH CHECK OPTION;
+GRANT SELECT ON TABLE reports.active_users TO ro_user;
+CREATE MATERIALIZED VIEW reports.users_by_role AS
+SELECT role, count(*) AS total FROM reports.users GROUP BY role;
+CREATE UNIQUE INDEX users_by_role_role_idx ON reports.users_by_role (role);
+ALTER MATERIALIZED VIEW reports.users_by_role OWNER TO rw_user;");
+}
+
+#[test]
+fn test_views_sqlite() {
+    let loader = Loader::new(VIEWS_YAML).expect("to load views");
+    let proc = Processor::new_with_objects(loader.objects(), Some(Box::new(Sqlite{})));
+    let sqls = proc.sql_statements();
+    assert_eq!(sqls.len(), 2);
+    assert_eq!(sqls[1], "CREATE VIEW active_users (id,name) AS
+SELECT id, user_name FROM reports.users WHERE NOT is_locked;");
+}
+
+#[test]
+fn test_materialized_view_refresh() {
+    let mut mv = MaterializedView::new(&ObjectPath::new_materialized_view("reports", "totals"), "SELECT 1 AS one");
+    assert_eq!(mv.refresh_sql(&Postgresql{}), "REFRESH MATERIALIZED VIEW reports.totals;");
+    mv.unique_index = Some(vec!["one".to_owned()]);
+    assert_eq!(mv.refresh_sql(&Postgresql{}), "REFRESH MATERIALIZED VIEW CONCURRENTLY reports.totals;");
+    assert_eq!(mv.refresh_sql(&Mysql{}), "");
+}
+
+#[test]
+fn test_views_without_otype() {
+    let loader = Loader::new("
+- tag: View
+  path: { schema: public, name: v }
+  query: SELECT 1
+- tag: MaterializedView
+  path: { schema: public, name: mv }
+  query: SELECT 1
+").expect("to load views");
+    let proc = Processor::new_with_objects(loader.objects(), None);
+    assert_eq!(proc.sql_statements(), vec![
+        "CREATE VIEW public.v AS\nSELECT 1;",
+        "CREATE MATERIALIZED VIEW public.mv AS\nSELECT 1;",
+    ]);
+    let drops:Vec<String> = loader.objects().iter().map(|o| o.drop_sql(&Postgresql{})).collect();
+    assert_eq!(drops, vec!["DROP VIEW public.v;", "DROP MATERIALIZED VIEW public.mv;"]);
+}
+
+#[test]
+fn test_views_dependency_order() {
+    let loader = Loader::new("
+- tag: View
+  path: { schema: r, name: c, otype: View }
+  query: SELECT * FROM r.b
+  depends_on: [{ schema: r, name: b, otype: View }]
+- tag: View
+  path: { schema: r, name: x, otype: View }
+  query: SELECT * FROM r.y
+  depends_on: [{ schema: r, name: y, otype: View }]
+- tag: View
+  path: { schema: r, name: b, otype: View }
+  query: SELECT * FROM r.a
+  depends_on: [{ schema: r, name: a, otype: View }]
+- tag: View
+  path: { schema: r, name: y, otype: View }
+  query: SELECT * FROM r.x
+  depends_on: [{ schema: r, name: x, otype: View }]
+- tag: View
+  path: { schema: r, name: a, otype: View }
+  query: SELECT 1 AS one
+").expect("to load views");
+    let proc = Processor::new_with_objects(loader.objects(), None);
+    let names:Vec<String> = proc.sql_statements().iter()
+        .map(|s| s.lines().next().unwrap_or_default().to_owned()).collect();
+    assert_eq!(names, vec![
+        "CREATE VIEW r.a AS",
+        "CREATE VIEW r.b AS",
+        "CREATE VIEW r.c AS",
+        "CREATE VIEW r.x AS",
+        "CREATE VIEW r.y AS",
+    ]);
+}
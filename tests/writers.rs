@@ -1,5 +1,6 @@
 use dml_tools::util::read_file_into_string;
 use dml_tools::type_writers::*;
+use dml_tools::sql::*;
 
 mod common;
 use common::*;
@@ -22,3 +23,38 @@ fn test_writer_sqlite() {
     let writer = Box::new(Sqlite{});
     assert_eq!(test_table_with_writer_to_string(writer.as_ref()), read_file_into_string("tests/fixtures/table-sqlite.sql"));
 }
+
+fn identity_table() -> Table {
+    let fields = vec![
+        Field::new("id", &FieldAttributes::new_pk(FieldType::AutoInc)),
+        Field::new("code", &FieldAttributes::new_identity_pk(&Identity::new(IdentityGeneration::Always, IdentityWidth::BigInt))),
+    ];
+    Table::new(&ObjectPath::new_table("myschema", "ids"), fields, None)
+}
+
+#[test]
+fn test_writer_identity() {
+    let table = identity_table();
+    assert_eq!(table.to_sql(&Postgresql{}), "CREATE TABLE myschema.ids (
+  id serial NOT NULL,
+  code bigint GENERATED ALWAYS AS IDENTITY NOT NULL,
+  CONSTRAINT ids_id_code_pk PRIMARY KEY (id,code)
+);");
+    assert_eq!(table.to_sql(&PostgresqlIdentity::default()), "CREATE TABLE myschema.ids (
+  id int GENERATED BY DEFAULT AS IDENTITY NOT NULL,
+  code bigint GENERATED ALWAYS AS IDENTITY NOT NULL,
+  CONSTRAINT ids_id_code_pk PRIMARY KEY (id,code)
+);");
+    assert_eq!(table.warnings(), vec!["myschema.ids has more than one AutoInc field, rejected by Mysql and Sqlite".to_owned()]);
+}
+
+#[test]
+fn test_writer_identity_mysql() {
+    let mut table = identity_table();
+    table.fields.remove(0);
+    assert!(table.warnings().is_empty());
+    assert_eq!(table.to_sql(&Mysql{}), "CREATE TABLE myschema.ids (
+  code bigint auto_increment NOT NULL,
+  CONSTRAINT ids_code_pk PRIMARY KEY (code)
+);");
+}